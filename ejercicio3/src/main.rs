@@ -6,22 +6,31 @@ mod agente; // Módulo para manejar al agente.
 mod operarios; // Módulo para la lógica de los operarios.
 mod montador; // Módulo para la lógica del montador.
 mod semaforo_operarios; // Módulo para la sincronización entre operarios y montador.
+mod metricas; // Módulo para el reporte de utilización de la línea de montaje.
 
 use std::sync::Arc;
 use tokio::sync::Notify;
 use std::io::{self, Write};
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 /// Variable global que define el número máximo de iteraciones para cada ejercicio.
 /// Controla cuántas veces los fumadores y los montadores ejecutan sus tareas.
 const NUMERO_ITERACIONES: usize = 5;
 
+/// Cantidad de bicicletas cuyas piezas caben en los depósitos de la línea de montaje.
+const CAPACIDAD_DEPOSITO: usize = 1;
+
 #[tokio::main]
 /// Función principal que maneja el menú interactivo para seleccionar entre
 /// los ejercicios de fumadores o montadores.
 ///
 /// Continúa ejecutándose en un bucle hasta que el usuario seleccione "Salir".
+/// El primer argumento de la línea de comandos, si se indica, es el directorio donde se
+/// exporta el reporte CSV de los montadores; por defecto, el directorio actual.
 async fn main() {
+    let directorio_reporte = std::env::args().nth(1).map_or_else(|| PathBuf::from("."), PathBuf::from);
+
     loop {
         // Despliega el menú principal
         println!("--- Menú Principal ---");
@@ -44,7 +53,7 @@ async fn main() {
             }
             "2" => {
                 println!("Iniciando el ejercicio de los montadores de bicicletas...");
-                ejecutar_montadores().await; // Llama al ejercicio de montadores
+                ejecutar_montadores(&directorio_reporte).await; // Llama al ejercicio de montadores
             }
             "3" => {
                 println!("Saliendo del programa...");
@@ -111,17 +120,22 @@ async fn ejecutar_fumadores() {
 /// Función que inicializa y ejecuta el ejercicio de los montadores.
 ///
 /// Esta función configura las tareas asincrónicas para los operarios y el montador,
-/// y las coordina utilizando semáforos.
-async fn ejecutar_montadores() {
+/// y las coordina utilizando semáforos. Al finalizar, imprime el reporte de utilización
+/// de la línea y lo exporta en formato CSV.
+///
+/// # Parámetros
+/// - `directorio_reporte`: Directorio donde se escriben los archivos CSV del reporte.
+async fn ejecutar_montadores(directorio_reporte: &Path) {
     // Configura la sincronización entre operarios y montador.
-    let sincronizacion: Arc<semaforo_operarios::SemaforOperarios> = Arc::new(semaforo_operarios::SemaforOperarios::new());
-    let mut handles: Vec<tokio::task::JoinHandle<()>> = vec![]; // Vector para almacenar las tareas.
+    let sincronizacion: Arc<semaforo_operarios::SemaforOperarios> = Arc::new(semaforo_operarios::SemaforOperarios::new(CAPACIDAD_DEPOSITO));
+    let mut handles: Vec<tokio::task::JoinHandle<metricas::MetricasActor>> = vec![]; // Vector para almacenar las tareas de los operarios.
+    let inicio = Instant::now(); // Instante de inicio de la simulación.
 
     // Tarea para el operario 1 (ruedas)
     handles.push(tokio::spawn({
         let sincronizacion = Arc::clone(&sincronizacion);
         async move {
-            operarios::op1(sincronizacion, NUMERO_ITERACIONES).await;
+            operarios::op1(sincronizacion, NUMERO_ITERACIONES).await
         }
     }));
 
@@ -129,7 +143,7 @@ async fn ejecutar_montadores() {
     handles.push(tokio::spawn({
         let sincronizacion = Arc::clone(&sincronizacion);
         async move {
-            operarios::op2(sincronizacion, NUMERO_ITERACIONES).await;
+            operarios::op2(sincronizacion, NUMERO_ITERACIONES).await
         }
     }));
 
@@ -137,20 +151,38 @@ async fn ejecutar_montadores() {
     handles.push(tokio::spawn({
         let sincronizacion = Arc::clone(&sincronizacion);
         async move {
-            operarios::op3(sincronizacion, NUMERO_ITERACIONES).await;
+            operarios::op3(sincronizacion, NUMERO_ITERACIONES).await
         }
     }));
 
     // Tarea para el montador
-    handles.push(tokio::spawn({
+    let montador = tokio::spawn({
         let sincronizacion = Arc::clone(&sincronizacion);
         async move {
-            montador::montador(sincronizacion, NUMERO_ITERACIONES).await;
+            montador::montador(sincronizacion, NUMERO_ITERACIONES).await
         }
-    }));
+    });
 
-    // Espera a que todas las tareas terminen.
+    // Espera a que todas las tareas terminen y recoge sus métricas.
+    let mut actores = Vec::with_capacity(handles.len() + 1);
     for handle in handles {
-        handle.await.unwrap();
+        actores.push(handle.await.unwrap());
+    }
+    let (metricas_montador, tiempos_entrega) = montador.await.unwrap();
+    actores.push(metricas_montador);
+
+    // Genera el reporte de la línea de producción.
+    let reporte = metricas::ReporteLinea {
+        duracion_total: inicio.elapsed(),
+        actores,
+        tiempos_entrega,
+    };
+    reporte.imprimir();
+    match reporte.exportar_csv(directorio_reporte) {
+        Ok(()) => println!(
+            "Reporte exportado a utilizacion_linea.csv y bicicletas_linea.csv en {}.",
+            directorio_reporte.display()
+        ),
+        Err(error) => println!("No se pudo exportar el reporte CSV: {}", error),
     }
 }
@@ -0,0 +1,255 @@
+use std::fs::File; // File para crear los archivos CSV del reporte.
+use std::io::{self, Write}; // Write para escribir las filas de los archivos CSV.
+use std::path::Path; // Path para indicar el directorio donde se exporta el reporte.
+use std::time::Duration; // Duration para representar los tiempos medidos.
+
+/// Estructura que acumula los tiempos medidos de un actor de la línea de producción.
+///
+/// Cada operario y el montador registran cuánto tiempo pasaron produciendo y cuánto
+/// tiempo estuvieron bloqueados esperando a la sincronización. El tiempo ocioso se
+/// deduce al final a partir de la duración total de la simulación.
+pub struct MetricasActor {
+    pub nombre: &'static str, // Nombre del actor (por ejemplo, "OP1" o "Montador").
+    pub produciendo: Duration, // Tiempo total dedicado a producir o ensamblar.
+    pub bloqueado: Duration,   // Tiempo total bloqueado esperando en los semáforos.
+}
+
+impl MetricasActor {
+    /// Constructor que inicializa las métricas de un actor con tiempos en cero.
+    ///
+    /// # Parámetros
+    /// - `nombre`: Nombre del actor que se mostrará en el reporte.
+    ///
+    /// # Retorno
+    /// Retorna una instancia de `MetricasActor` sin tiempo acumulado.
+    pub fn new(nombre: &'static str) -> Self {
+        Self {
+            nombre,
+            produciendo: Duration::ZERO,
+            bloqueado: Duration::ZERO,
+        }
+    }
+
+    /// Calcula el tiempo ocioso del actor respecto a la duración total de la simulación.
+    ///
+    /// # Parámetros
+    /// - `total`: Duración total de la simulación.
+    ///
+    /// # Retorno
+    /// Retorna el tiempo en el que el actor no produjo ni estuvo bloqueado.
+    pub fn ocioso(&self, total: Duration) -> Duration {
+        total.saturating_sub(self.produciendo + self.bloqueado)
+    }
+}
+
+/// Estructura que reúne las métricas de toda la línea de producción de bicicletas.
+///
+/// Contiene los tiempos de cada actor, el tiempo de entrega de cada bicicleta (desde que
+/// se produjo su primera pieza hasta que quedó ensamblada) y la duración total de la corrida.
+pub struct ReporteLinea {
+    pub duracion_total: Duration,       // Duración total de la simulación.
+    pub actores: Vec<MetricasActor>,    // Métricas de los operarios y del montador.
+    pub tiempos_entrega: Vec<Duration>, // Tiempo de entrega de cada bicicleta, en orden de ensamblaje.
+}
+
+impl ReporteLinea {
+    /// Calcula el rendimiento de la línea en bicicletas por minuto simulado.
+    ///
+    /// # Retorno
+    /// Retorna la cantidad de bicicletas terminadas por minuto, o 0 si la simulación no duró nada.
+    pub fn bicicletas_por_minuto(&self) -> f64 {
+        let segundos = self.duracion_total.as_secs_f64();
+        if segundos == 0.0 {
+            return 0.0;
+        }
+        self.tiempos_entrega.len() as f64 * 60.0 / segundos
+    }
+
+    /// Calcula el tiempo de entrega promedio de las bicicletas ensambladas.
+    ///
+    /// # Retorno
+    /// Retorna el promedio de los tiempos de entrega, o cero si no se ensambló ninguna bicicleta.
+    pub fn tiempo_entrega_promedio(&self) -> Duration {
+        if self.tiempos_entrega.is_empty() {
+            return Duration::ZERO;
+        }
+        self.tiempos_entrega.iter().sum::<Duration>() / self.tiempos_entrega.len() as u32
+    }
+
+    /// Imprime en consola la tabla de utilización, los tiempos de entrega y el rendimiento.
+    pub fn imprimir(&self) {
+        let total = self.duracion_total;
+
+        println!("--- Reporte de utilización de la línea ---");
+        println!(
+            "{:<10} {:>12} {:>12} {:>12} {:>12}",
+            "Actor", "Produciendo", "Bloqueado", "Ocioso", "Utilización"
+        );
+        for actor in &self.actores {
+            println!(
+                "{:<10} {:>11.2}s {:>11.2}s {:>11.2}s {:>11.1}%",
+                actor.nombre,
+                actor.produciendo.as_secs_f64(),
+                actor.bloqueado.as_secs_f64(),
+                actor.ocioso(total).as_secs_f64(),
+                porcentaje(actor.produciendo, total)
+            );
+        }
+
+        println!("--- Tiempo de entrega por bicicleta ---");
+        for (indice, tiempo) in self.tiempos_entrega.iter().enumerate() {
+            println!("Bicicleta {}: {:.2}s", indice + 1, tiempo.as_secs_f64());
+        }
+        println!(
+            "Tiempo de entrega promedio: {:.2}s",
+            self.tiempo_entrega_promedio().as_secs_f64()
+        );
+        println!(
+            "Rendimiento: {:.2} bicicletas por minuto ({} bicicletas en {:.2}s)",
+            self.bicicletas_por_minuto(),
+            self.tiempos_entrega.len(),
+            total.as_secs_f64()
+        );
+    }
+
+    /// Exporta el reporte a dos archivos CSV dentro del directorio indicado.
+    ///
+    /// Se generan `utilizacion_linea.csv`, con una fila por actor, y `bicicletas_linea.csv`,
+    /// con el tiempo de entrega de cada bicicleta.
+    ///
+    /// # Parámetros
+    /// - `directorio`: Directorio donde se crearán los archivos.
+    ///
+    /// # Retorno
+    /// Retorna `Ok(())` si ambos archivos se escribieron correctamente, o el error de E/S ocurrido.
+    pub fn exportar_csv(&self, directorio: &Path) -> io::Result<()> {
+        let mut utilizacion = File::create(directorio.join("utilizacion_linea.csv"))?;
+        self.escribir_utilizacion_csv(&mut utilizacion)?;
+
+        let mut bicicletas = File::create(directorio.join("bicicletas_linea.csv"))?;
+        self.escribir_bicicletas_csv(&mut bicicletas)
+    }
+
+    /// Escribe la tabla de utilización en formato CSV, con una fila por actor.
+    ///
+    /// # Parámetros
+    /// - `salida`: Destino de las filas.
+    ///
+    /// # Retorno
+    /// Retorna `Ok(())` si todas las filas se escribieron, o el error de E/S ocurrido.
+    fn escribir_utilizacion_csv(&self, salida: &mut impl Write) -> io::Result<()> {
+        let total = self.duracion_total;
+
+        writeln!(salida, "actor,produciendo_s,bloqueado_s,ocioso_s,utilizacion_pct")?;
+        for actor in &self.actores {
+            writeln!(
+                salida,
+                "{},{:.3},{:.3},{:.3},{:.2}",
+                actor.nombre,
+                actor.produciendo.as_secs_f64(),
+                actor.bloqueado.as_secs_f64(),
+                actor.ocioso(total).as_secs_f64(),
+                porcentaje(actor.produciendo, total)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Escribe el tiempo de entrega de cada bicicleta en formato CSV.
+    ///
+    /// # Parámetros
+    /// - `salida`: Destino de las filas.
+    ///
+    /// # Retorno
+    /// Retorna `Ok(())` si todas las filas se escribieron, o el error de E/S ocurrido.
+    fn escribir_bicicletas_csv(&self, salida: &mut impl Write) -> io::Result<()> {
+        writeln!(salida, "bicicleta,tiempo_entrega_s")?;
+        for (indice, tiempo) in self.tiempos_entrega.iter().enumerate() {
+            writeln!(salida, "{},{:.3}", indice + 1, tiempo.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+/// Calcula qué porcentaje de `total` representa `parte`.
+///
+/// # Parámetros
+/// - `parte`: Duración parcial.
+/// - `total`: Duración de referencia.
+///
+/// # Retorno
+/// Retorna el porcentaje en el rango 0-100, o 0 si `total` es cero.
+fn porcentaje(parte: Duration, total: Duration) -> f64 {
+    if total.is_zero() {
+        return 0.0;
+    }
+    parte.as_secs_f64() * 100.0 / total.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actor(nombre: &'static str, produciendo: u64, bloqueado: u64) -> MetricasActor {
+        MetricasActor {
+            nombre,
+            produciendo: Duration::from_secs(produciendo),
+            bloqueado: Duration::from_secs(bloqueado),
+        }
+    }
+
+    fn reporte() -> ReporteLinea {
+        ReporteLinea {
+            duracion_total: Duration::from_secs(40),
+            actores: vec![actor("OP1", 20, 6), actor("Montador", 16, 24)],
+            tiempos_entrega: vec![Duration::from_millis(9500), Duration::from_millis(12500)],
+        }
+    }
+
+    #[test]
+    fn ocioso_es_lo_que_no_se_produjo_ni_se_espero() {
+        let total = Duration::from_secs(40);
+        assert_eq!(actor("OP1", 20, 6).ocioso(total), Duration::from_secs(14));
+        assert_eq!(actor("Montador", 16, 24).ocioso(total), Duration::ZERO);
+        // Un actor medido con más tiempo que la corrida no da un ocioso negativo.
+        assert_eq!(actor("OP2", 30, 15).ocioso(total), Duration::ZERO);
+    }
+
+    #[test]
+    fn porcentaje_de_utilizacion() {
+        assert_eq!(porcentaje(Duration::from_secs(10), Duration::from_secs(40)), 25.0);
+        assert_eq!(porcentaje(Duration::from_secs(10), Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn rendimiento_y_tiempo_de_entrega() {
+        let reporte = reporte();
+        assert_eq!(reporte.bicicletas_por_minuto(), 3.0);
+        assert_eq!(reporte.tiempo_entrega_promedio(), Duration::from_secs(11));
+
+        let vacio = ReporteLinea { duracion_total: Duration::ZERO, actores: vec![], tiempos_entrega: vec![] };
+        assert_eq!(vacio.bicicletas_por_minuto(), 0.0);
+        assert_eq!(vacio.tiempo_entrega_promedio(), Duration::ZERO);
+    }
+
+    #[test]
+    fn filas_csv() {
+        let reporte = reporte();
+
+        let mut utilizacion = Vec::new();
+        reporte.escribir_utilizacion_csv(&mut utilizacion).unwrap();
+        assert_eq!(
+            String::from_utf8(utilizacion).unwrap(),
+            "actor,produciendo_s,bloqueado_s,ocioso_s,utilizacion_pct\n\
+             OP1,20.000,6.000,14.000,50.00\n\
+             Montador,16.000,24.000,0.000,40.00\n"
+        );
+
+        let mut bicicletas = Vec::new();
+        reporte.escribir_bicicletas_csv(&mut bicicletas).unwrap();
+        assert_eq!(
+            String::from_utf8(bicicletas).unwrap(),
+            "bicicleta,tiempo_entrega_s\n1,9.500\n2,12.500\n"
+        );
+    }
+}
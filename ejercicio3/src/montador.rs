@@ -1,6 +1,8 @@
 use std::sync::Arc; // Arc para compartir datos entre tareas asincrónicas.
 use tokio::time::{sleep, Duration}; // Herramientas de Tokio para manejar tiempos asincrónicos.
+use std::time::Instant; // Instant para medir el tiempo de ensamblaje, de espera y de entrega.
 use crate::semaforo_operarios::SemaforOperarios; // Importa el semáforo personalizado para coordinar a los operarios y al montador.
+use crate::metricas::MetricasActor; // Métricas de tiempo que devuelve el montador.
 
 /// Función asincrónica que representa la lógica del montador en el problema de ensamblaje.
 ///
@@ -15,17 +17,29 @@ use crate::semaforo_operarios::SemaforOperarios; // Importa el semáforo persona
 /// - Espera a que las piezas necesarias estén disponibles.
 /// - Ensambla la bicicleta.
 /// - Repite el proceso hasta completar las iteraciones especificadas.
-pub async fn montador(sync: Arc<SemaforOperarios>, max_iteraciones: usize) {
+///
+/// # Retorno
+/// Retorna las métricas de tiempo del montador junto con el tiempo de entrega de cada
+/// bicicleta, medido desde que se produjo su primera pieza hasta que quedó lista.
+pub async fn montador(sync: Arc<SemaforOperarios>, max_iteraciones: usize) -> (MetricasActor, Vec<Duration>) {
+    let mut metricas = MetricasActor::new("Montador");
+    let mut tiempos_entrega = Vec::with_capacity(max_iteraciones);
+
     // Bucle que controla el número de iteraciones del montador.
     for iteracion in 1..=max_iteraciones {
         println!("Montador (Iteración {}/{}): Esperando piezas...", iteracion, max_iteraciones);
 
         // Espera las piezas necesarias: dos ruedas, un cuadro y un manillar.
-        sync.esperar_piezas().await;
+        let inicio = Instant::now();
+        let primera_pieza = sync.esperar_piezas().await;
+        metricas.bloqueado += inicio.elapsed();
 
         // Simula el proceso de ensamblaje de la bicicleta.
         println!("Montador (Iteración {}/{}): Armando bicicleta...", iteracion, max_iteraciones);
+        let inicio = Instant::now();
         sleep(Duration::from_secs(4)).await; // Simula el tiempo necesario para ensamblar la bicicleta.
+        metricas.produciendo += inicio.elapsed();
+        tiempos_entrega.push(primera_pieza.elapsed());
 
         // Indica que la bicicleta está lista.
         println!("Montador (Iteración {}/{}): Bicicleta lista.", iteracion, max_iteraciones);
@@ -33,4 +47,5 @@ pub async fn montador(sync: Arc<SemaforOperarios>, max_iteraciones: usize) {
 
     // Mensaje final indicando que el montador completó todas las iteraciones.
     println!("Montador: Completó las {} iteraciones. Terminando trabajo.", max_iteraciones);
+    (metricas, tiempos_entrega)
 }
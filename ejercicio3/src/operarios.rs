@@ -1,32 +1,50 @@
 use std::sync::Arc; // Arc para compartir datos entre tareas asincrónicas.
 use tokio::time::{sleep, Duration}; // Herramientas de Tokio para manejar tiempos asincrónicos.
+use std::time::Instant; // Instant para medir el tiempo de producción y de espera.
 use crate::semaforo_operarios::SemaforOperarios; // Importa el semáforo personalizado para coordinar a los operarios y el montador.
+use crate::metricas::MetricasActor; // Métricas de tiempo que devuelve cada operario.
 
 /// Función asincrónica que representa la lógica del Operario 1 (OP1).
 ///
 /// OP1 produce ruedas y notifica al montador cuando cada rueda está lista.
+/// Como cada bicicleta lleva dos ruedas, en cada iteración produce un par: el montador
+/// consume dos ruedas por bicicleta, así que con una sola rueda por iteración OP1 terminaría
+/// habiendo fabricado ruedas para la mitad de las bicicletas y el montador esperaría para siempre.
 ///
 /// # Parámetros
 /// - `sync`: Referencia compartida al semáforo que coordina las acciones entre operarios y montador.
-/// - `max_iteraciones`: Número máximo de ruedas que producirá OP1.
+/// - `max_iteraciones`: Número máximo de pares de ruedas que producirá OP1.
 ///
 /// # Comportamiento
-/// - Produce una rueda.
-/// - Notifica al montador que una rueda está lista.
+/// - Produce dos ruedas, una tras otra.
+/// - Deja cada rueda en el depósito, esperando si está lleno, y notifica al montador.
 /// - Repite el proceso hasta completar las iteraciones especificadas.
-pub async fn op1(sync: Arc<SemaforOperarios>, max_iteraciones: usize) {
+///
+/// # Retorno
+/// Retorna las métricas de tiempo de OP1.
+pub async fn op1(sync: Arc<SemaforOperarios>, max_iteraciones: usize) -> MetricasActor {
+    let mut metricas = MetricasActor::new("OP1");
+
     for iteracion in 1..=max_iteraciones {
-        // Simula la producción de una rueda.
-        println!("OP1 (Iteración {}/{}): Produciendo una rueda...", iteracion, max_iteraciones);
-        sleep(Duration::from_secs(2)).await;
+        for _ in 0..2 {
+            // Simula la producción de una rueda.
+            println!("OP1 (Iteración {}/{}): Produciendo una rueda...", iteracion, max_iteraciones);
+            let inicio = Instant::now();
+            sleep(Duration::from_secs(2)).await;
+            metricas.produciendo += inicio.elapsed();
 
-        // Notifica al montador que una rueda está lista.
-        sync.rueda_producida().await;
-        println!("OP1 (Iteración {}/{}): Rueda lista.", iteracion, max_iteraciones);
+            // Espera lugar en el depósito y notifica al montador que una rueda está lista.
+            // El tiempo de espera por el depósito lleno cuenta como bloqueado.
+            let inicio = Instant::now();
+            sync.rueda_producida().await;
+            metricas.bloqueado += inicio.elapsed();
+            println!("OP1 (Iteración {}/{}): Rueda lista.", iteracion, max_iteraciones);
+        }
     }
 
     // Mensaje final indicando que OP1 completó todas las iteraciones.
     println!("OP1: Completó sus {} iteraciones y se detiene.", max_iteraciones);
+    metricas
 }
 
 /// Función asincrónica que representa la lógica del Operario 2 (OP2).
@@ -39,21 +57,32 @@ pub async fn op1(sync: Arc<SemaforOperarios>, max_iteraciones: usize) {
 ///
 /// # Comportamiento
 /// - Produce un cuadro.
-/// - Notifica al montador que un cuadro está listo.
+/// - Deja el cuadro en el depósito, esperando si está lleno, y notifica al montador.
 /// - Repite el proceso hasta completar las iteraciones especificadas.
-pub async fn op2(sync: Arc<SemaforOperarios>, max_iteraciones: usize) {
+///
+/// # Retorno
+/// Retorna las métricas de tiempo de OP2.
+pub async fn op2(sync: Arc<SemaforOperarios>, max_iteraciones: usize) -> MetricasActor {
+    let mut metricas = MetricasActor::new("OP2");
+
     for iteracion in 1..=max_iteraciones {
         // Simula la producción de un cuadro.
         println!("OP2 (Iteración {}/{}): Produciendo un cuadro...", iteracion, max_iteraciones);
+        let inicio = Instant::now();
         sleep(Duration::from_secs(3)).await;
+        metricas.produciendo += inicio.elapsed();
 
-        // Notifica al montador que un cuadro está listo.
+        // Espera lugar en el depósito y notifica al montador que un cuadro está listo.
+        // El tiempo de espera por el depósito lleno cuenta como bloqueado.
+        let inicio = Instant::now();
         sync.cuadro_producido().await;
+        metricas.bloqueado += inicio.elapsed();
         println!("OP2 (Iteración {}/{}): Cuadro listo.", iteracion, max_iteraciones);
     }
 
     // Mensaje final indicando que OP2 completó todas las iteraciones.
     println!("OP2: Completó sus {} iteraciones y se detiene.", max_iteraciones);
+    metricas
 }
 
 /// Función asincrónica que representa la lógica del Operario 3 (OP3).
@@ -66,19 +95,30 @@ pub async fn op2(sync: Arc<SemaforOperarios>, max_iteraciones: usize) {
 ///
 /// # Comportamiento
 /// - Produce un manillar.
-/// - Notifica al montador que un manillar está listo.
+/// - Deja el manillar en el depósito, esperando si está lleno, y notifica al montador.
 /// - Repite el proceso hasta completar las iteraciones especificadas.
-pub async fn op3(sync: Arc<SemaforOperarios>, max_iteraciones: usize) {
+///
+/// # Retorno
+/// Retorna las métricas de tiempo de OP3.
+pub async fn op3(sync: Arc<SemaforOperarios>, max_iteraciones: usize) -> MetricasActor {
+    let mut metricas = MetricasActor::new("OP3");
+
     for iteracion in 1..=max_iteraciones {
         // Simula la producción de un manillar.
         println!("OP3 (Iteración {}/{}): Produciendo un manillar...", iteracion, max_iteraciones);
+        let inicio = Instant::now();
         sleep(Duration::from_secs(1)).await;
+        metricas.produciendo += inicio.elapsed();
 
-        // Notifica al montador que un manillar está listo.
+        // Espera lugar en el depósito y notifica al montador que un manillar está listo.
+        // El tiempo de espera por el depósito lleno cuenta como bloqueado.
+        let inicio = Instant::now();
         sync.manillar_producido().await;
+        metricas.bloqueado += inicio.elapsed();
         println!("OP3 (Iteración {}/{}): Manillar lista.", iteracion, max_iteraciones);
     }
 
     // Mensaje final indicando que OP3 completó todas las iteraciones.
     println!("OP3: Completó sus {} iteraciones y se detiene.", max_iteraciones);
+    metricas
 }
@@ -51,28 +51,28 @@ impl SemphoreSmoker {
     ///
     /// Bloquea hasta que el semáforo correspondiente tenga permisos disponibles.
     pub async fn esperar_papel_tabaco(&self) {
-        self.papel_tabaco.acquire().await.unwrap(); // Adquiere un permiso de papel y tabaco.
+        let _ = self.papel_tabaco.acquire().await.unwrap(); // Adquiere un permiso de papel y tabaco.
     }
 
     /// Método para que un fumador con tabaco espere por papel y fósforos.
     ///
     /// Bloquea hasta que el semáforo correspondiente tenga permisos disponibles.
     pub async fn esperar_papel_fosforos(&self) {
-        self.papel_fosforos.acquire().await.unwrap(); // Adquiere un permiso de papel y fósforos.
+        let _ = self.papel_fosforos.acquire().await.unwrap(); // Adquiere un permiso de papel y fósforos.
     }
 
     /// Método para que un fumador con papel espere por tabaco y fósforos.
     ///
     /// Bloquea hasta que el semáforo correspondiente tenga permisos disponibles.
     pub async fn esperar_tabaco_fosforos(&self) {
-        self.tabaco_fosforos.acquire().await.unwrap(); // Adquiere un permiso de tabaco y fósforos.
+        let _ = self.tabaco_fosforos.acquire().await.unwrap(); // Adquiere un permiso de tabaco y fósforos.
     }
 
     /// Método para que el agente espere a que un fumador termine de fumar.
     ///
     /// Bloquea al agente hasta que un fumador lo notifique.
     pub async fn esperar_fumador(&self) {
-        self.agente.acquire().await.unwrap(); // Adquiere un permiso del semáforo del agente.
+        let _ = self.agente.acquire().await.unwrap(); // Adquiere un permiso del semáforo del agente.
     }
 
     /// Método para notificar al agente que un fumador terminó de fumar.
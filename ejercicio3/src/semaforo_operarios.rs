@@ -1,47 +1,89 @@
 use tokio::sync::Semaphore; // Semaphore de Tokio para manejar la concurrencia asincrónica.
-use std::sync::Arc; // Arc para compartir datos entre tareas asincrónicas.
+use std::collections::VecDeque; // VecDeque para guardar en orden los instantes de producción de cada pieza.
+use std::sync::{Arc, Mutex}; // Arc para compartir datos entre tareas asincrónicas y Mutex para las marcas de tiempo.
+use std::time::Instant; // Instant para registrar cuándo se produjo cada pieza.
+
+/// Estructura que guarda, por tipo de pieza, el instante en que se produjo cada pieza disponible.
+///
+/// Las marcas se consumen en el mismo orden en que se producen, igual que los permisos
+/// de los semáforos, de modo que el montador sabe cuándo se fabricó cada pieza que toma.
+struct MarcasPiezas {
+    ruedas: VecDeque<Instant>,     // Instantes de producción de las ruedas disponibles.
+    cuadros: VecDeque<Instant>,    // Instantes de producción de los cuadros disponibles.
+    manillares: VecDeque<Instant>, // Instantes de producción de los manillares disponibles.
+}
 
 /// Estructura que representa el sistema de sincronización para operarios y el montador.
 ///
 /// Este sistema utiliza semáforos para coordinar la producción de piezas por parte
 /// de los operarios (ruedas, cuadros y manillares) y su ensamblaje por el montador.
+/// Cada tipo de pieza se deja en un depósito de capacidad limitada: un operario que
+/// termina una pieza con el depósito lleno se bloquea hasta que el montador retire piezas.
 pub struct SemaforOperarios {
     ruedas: Arc<Semaphore>,   // Semáforo para controlar la disponibilidad de ruedas.
     cuadros: Arc<Semaphore>,  // Semáforo para controlar la disponibilidad de cuadros.
     manillares: Arc<Semaphore>, // Semáforo para controlar la disponibilidad de manillares.
+    huecos_ruedas: Arc<Semaphore>,     // Semáforo con los lugares libres del depósito de ruedas.
+    huecos_cuadros: Arc<Semaphore>,    // Semáforo con los lugares libres del depósito de cuadros.
+    huecos_manillares: Arc<Semaphore>, // Semáforo con los lugares libres del depósito de manillares.
+    marcas: Mutex<MarcasPiezas>, // Instantes de producción de las piezas que aún no se ensamblaron.
 }
 
 impl SemaforOperarios {
     /// Constructor que inicializa un nuevo sistema de sincronización.
     ///
+    /// # Parámetros
+    /// - `capacidad`: Cantidad de bicicletas cuyas piezas caben en los depósitos. El depósito
+    ///   de ruedas admite el doble, ya que cada bicicleta lleva dos.
+    ///
     /// # Retorno
-    /// Retorna una instancia de `SemaforOperarios` con todos los semáforos inicializados a 0 permisos.
-    pub fn new() -> Self {
+    /// Retorna una instancia de `SemaforOperarios` sin piezas disponibles y con los depósitos vacíos.
+    pub fn new(capacidad: usize) -> Self {
         Self {
             ruedas: Arc::new(Semaphore::new(0)),     // Inicialmente, no hay ruedas disponibles.
             cuadros: Arc::new(Semaphore::new(0)),    // Inicialmente, no hay cuadros disponibles.
             manillares: Arc::new(Semaphore::new(0)), // Inicialmente, no hay manillares disponibles.
+            huecos_ruedas: Arc::new(Semaphore::new(2 * capacidad)), // Inicialmente, el depósito de ruedas está vacío.
+            huecos_cuadros: Arc::new(Semaphore::new(capacidad)),    // Inicialmente, el depósito de cuadros está vacío.
+            huecos_manillares: Arc::new(Semaphore::new(capacidad)), // Inicialmente, el depósito de manillares está vacío.
+            marcas: Mutex::new(MarcasPiezas {
+                ruedas: VecDeque::new(),
+                cuadros: VecDeque::new(),
+                manillares: VecDeque::new(),
+            }),
         }
     }
 
     /// Método para notificar que una rueda ha sido producida.
     ///
-    /// Incrementa el semáforo correspondiente para indicar que una rueda está lista.
+    /// Espera un lugar libre en el depósito de ruedas, bloqueándose mientras esté lleno.
+    /// Luego registra el instante de producción e incrementa el semáforo correspondiente
+    /// para indicar que una rueda está lista.
     pub async fn rueda_producida(&self) {
+        self.huecos_ruedas.acquire().await.unwrap().forget(); // Ocupa un lugar del depósito.
+        self.marcas.lock().unwrap().ruedas.push_back(Instant::now());
         self.ruedas.add_permits(1); // Añade un permiso para ruedas.
     }
 
     /// Método para notificar que un cuadro ha sido producido.
     ///
-    /// Incrementa el semáforo correspondiente para indicar que un cuadro está listo.
+    /// Espera un lugar libre en el depósito de cuadros, bloqueándose mientras esté lleno.
+    /// Luego registra el instante de producción e incrementa el semáforo correspondiente
+    /// para indicar que un cuadro está listo.
     pub async fn cuadro_producido(&self) {
+        self.huecos_cuadros.acquire().await.unwrap().forget(); // Ocupa un lugar del depósito.
+        self.marcas.lock().unwrap().cuadros.push_back(Instant::now());
         self.cuadros.add_permits(1); // Añade un permiso para cuadros.
     }
 
     /// Método para notificar que un manillar ha sido producido.
     ///
-    /// Incrementa el semáforo correspondiente para indicar que un manillar está listo.
+    /// Espera un lugar libre en el depósito de manillares, bloqueándose mientras esté lleno.
+    /// Luego registra el instante de producción e incrementa el semáforo correspondiente
+    /// para indicar que un manillar está listo.
     pub async fn manillar_producido(&self) {
+        self.huecos_manillares.acquire().await.unwrap().forget(); // Ocupa un lugar del depósito.
+        self.marcas.lock().unwrap().manillares.push_back(Instant::now());
         self.manillares.add_permits(1); // Añade un permiso para manillares.
     }
 
@@ -51,15 +93,35 @@ impl SemaforOperarios {
     /// - Hay al menos 2 ruedas disponibles.
     /// - Hay al menos 1 cuadro disponible.
     /// - Hay al menos 1 manillar disponible.
-    pub async fn esperar_piezas(&self) {
-        // Adquiere permisos para dos ruedas.
-        self.ruedas.acquire().await.unwrap();
-        self.ruedas.acquire().await.unwrap();
+    ///
+    /// Los permisos adquiridos se consumen, ya que las piezas pasan a formar parte de la bicicleta,
+    /// y los lugares que ocupaban en los depósitos quedan libres para los operarios.
+    ///
+    /// # Retorno
+    /// Retorna el instante en que se produjo la primera de las piezas tomadas.
+    pub async fn esperar_piezas(&self) -> Instant {
+        // Adquiere y consume permisos para dos ruedas.
+        self.ruedas.acquire_many(2).await.unwrap().forget();
+
+        // Adquiere y consume un permiso para un cuadro.
+        self.cuadros.acquire().await.unwrap().forget();
+
+        // Adquiere y consume un permiso para un manillar.
+        self.manillares.acquire().await.unwrap().forget();
 
-        // Adquiere un permiso para un cuadro.
-        self.cuadros.acquire().await.unwrap();
+        // Las piezas salen de los depósitos, dejando sus lugares libres para los operarios.
+        self.huecos_ruedas.add_permits(2);
+        self.huecos_cuadros.add_permits(1);
+        self.huecos_manillares.add_permits(1);
 
-        // Adquiere un permiso para un manillar.
-        self.manillares.acquire().await.unwrap();
+        // Retira las marcas de las piezas tomadas y devuelve la más antigua.
+        let mut marcas = self.marcas.lock().unwrap();
+        let piezas = [
+            marcas.ruedas.pop_front(),
+            marcas.ruedas.pop_front(),
+            marcas.cuadros.pop_front(),
+            marcas.manillares.pop_front(),
+        ];
+        piezas.into_iter().flatten().min().unwrap()
     }
 }
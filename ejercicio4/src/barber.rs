@@ -1,27 +1,197 @@
-use std::sync::Arc;
-use tokio::sync::{Mutex, Semaphore};
+use std::collections::VecDeque;
+use tokio::sync::{oneshot, Mutex, Semaphore};
+use tokio::time::{Duration, Instant};
+use crate::distribution::TimeDistribution;
 
-/// Represents a barber shop with a limited number of chairs and customers.
+/// Parameters of a barber shop simulation.
+#[derive(Clone, Debug)]
+pub struct ShopConfig {
+    pub barbers: u32,                        // Number of barbers
+    pub chairs: u32,                         // Number of chairs in the waiting room
+    pub customers: u32,                      // Total number of customers that will arrive
+    pub interarrival: TimeDistribution,      // Time between two consecutive arrivals
+    pub service: TimeDistribution,           // Time a haircut takes
+    pub patience: Option<TimeDistribution>,  // How long a customer waits before reneging, if at all
+    pub vip_probability: f64,                // Probability that a customer is VIP
+    pub seed: u64,                           // Seed for every random draw of the run
+    pub time_scale: f64,                     // Factor applied to every duration, below 1 to compress time
+}
+
+impl Default for ShopConfig {
+    fn default() -> Self {
+        ShopConfig {
+            barbers: 2,
+            chairs: 3,
+            customers: 20,
+            interarrival: TimeDistribution::Exponential { mean: Duration::from_millis(1000) },
+            service: TimeDistribution::Exponential { mean: Duration::from_millis(1500) },
+            patience: Some(TimeDistribution::Exponential { mean: Duration::from_secs(3) }),
+            vip_probability: 0.25,
+            seed: 42,
+            time_scale: 1.0,
+        }
+    }
+}
+
+impl ShopConfig {
+    /// Checks that the parameters describe a shop that can be simulated.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.barbers == 0 {
+            return Err("la barbería necesita al menos un barbero".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.vip_probability) {
+            return Err(format!(
+                "la probabilidad de cliente VIP debe estar entre 0 y 1 (se recibió {})",
+                self.vip_probability
+            ));
+        }
+        if !(self.time_scale.is_finite() && self.time_scale > 0.0) {
+            return Err(format!(
+                "la escala de tiempo debe ser un número positivo (se recibió {})",
+                self.time_scale
+            ));
+        }
+        if self.service.mean().is_zero() {
+            return Err("el tiempo medio de corte debe ser mayor que cero".to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the same configuration with every distribution scaled by `time_scale`,
+    /// and a scale of one.
+    pub fn compressed(&self) -> ShopConfig {
+        ShopConfig {
+            interarrival: self.interarrival.scaled(self.time_scale),
+            service: self.service.scaled(self.time_scale),
+            patience: self.patience.map(|patience| patience.scaled(self.time_scale)),
+            time_scale: 1.0,
+            ..self.clone()
+        }
+    }
+}
+
+/// Class of a customer. VIP customers are served before regular ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CustomerClass {
+    Regular,
+    Vip,
+}
+
+/// How a customer's visit to the barber shop ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CustomerOutcome {
+    Served,   // Got a haircut
+    Balked,   // Left on arrival because the waiting room was full
+    Reneged,  // Left after running out of patience
+}
+
+/// A customer sitting in one of the waiting chairs.
+pub struct WaitingCustomer {
+    pub id: u32,                     // ID of the customer
+    pub arrived_at: Instant,         // When the customer sat down
+    pub service_time: Duration,      // How long this customer's haircut takes
+    pub done: oneshot::Sender<()>,   // Signalled when this customer's haircut is finished
+}
+
+/// Counters kept for each customer class.
+#[derive(Default)]
+pub struct ClassStats {
+    pub served: u32,          // Customers that got a haircut
+    pub balked: u32,          // Customers that left on arrival because the room was full
+    pub reneged: u32,         // Customers that left after running out of patience
+    pub total_wait: Duration, // Sum of the waiting times of the served customers
+}
+
+/// Shared state of the barber shop, protected by a single mutex.
+pub struct ShopState {
+    pub vip_waiting: VecDeque<WaitingCustomer>,     // VIP customers in the waiting room, in arrival order
+    pub regular_waiting: VecDeque<WaitingCustomer>, // Regular customers in the waiting room, in arrival order
+    pub remaining_customers: u32,                   // Customers that have not been served nor left yet
+    pub vip_stats: ClassStats,                      // Counters for VIP customers
+    pub regular_stats: ClassStats,                  // Counters for regular customers
+    pub opened_at: Instant,                         // When the shop opened
+    pub closed_at: Option<Instant>,                 // When the last customer was served or left
+    pub last_queue_change: Instant,                 // Last time the waiting room length changed
+    pub queue_area: f64,                            // Integral of the waiting room length over time, in customer-seconds
+    pub busy_time: Duration,                        // Sum of the time spent cutting hair by all barbers
+}
+
+impl ShopState {
+    /// Returns the number of customers sitting in the waiting room.
+    pub fn waiting_len(&self) -> usize {
+        self.vip_waiting.len() + self.regular_waiting.len()
+    }
+
+    /// Accumulates the waiting room length since the last change. Must be called right
+    /// before the waiting room length changes.
+    pub fn record_queue_change(&mut self) {
+        let now = Instant::now();
+        self.queue_area += self.waiting_len() as f64 * (now - self.last_queue_change).as_secs_f64();
+        self.last_queue_change = now;
+    }
+
+    /// Returns the waiting queue of the given class.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - The customer class.
+    pub fn queue_mut(&mut self, class: CustomerClass) -> &mut VecDeque<WaitingCustomer> {
+        match class {
+            CustomerClass::Vip => &mut self.vip_waiting,
+            CustomerClass::Regular => &mut self.regular_waiting,
+        }
+    }
+
+    /// Returns the counters of the given class.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - The customer class.
+    pub fn stats_mut(&mut self, class: CustomerClass) -> &mut ClassStats {
+        match class {
+            CustomerClass::Vip => &mut self.vip_stats,
+            CustomerClass::Regular => &mut self.regular_stats,
+        }
+    }
+}
+
+/// Represents a barber shop with several barbers, a limited waiting room and a known number of customers.
 pub struct BarberShop {
-    pub semaphore: Arc<Semaphore>,                // Semaphore to manage available chairs
-    pub max_chairs: u32,                          // Maximum number of chairs
-    pub customers_waiting: Arc<Mutex<u32>>,       // Number of customers waiting
-    pub remaining_customers: Arc<Mutex<u32>>,     // Total remaining customers
+    pub customers: Semaphore,      // One permit per waiting customer, plus one per barber when the shop closes
+    pub barbers: u32,              // Number of barbers working in the shop
+    pub max_chairs: u32,           // Number of chairs in the waiting room
+    pub state: Mutex<ShopState>,   // Waiting room and bookkeeping
 }
 
 impl BarberShop {
-    /// Creates a new `BarberShop` with the specified number of chairs and total customers.
+    /// Creates a new `BarberShop` with the specified number of barbers, chairs and total customers.
     ///
     /// # Arguments
     ///
-    /// * `max_chairs` - The maximum number of chairs available in the barber shop.
+    /// * `barbers` - The number of barbers cutting hair.
+    /// * `max_chairs` - The number of chairs in the waiting room.
     /// * `total_customers` - The total number of customers expected.
-    pub fn new(max_chairs: u32, total_customers: u32) -> Self {
+    pub fn new(barbers: u32, max_chairs: u32, total_customers: u32) -> Self {
+        // With no customers expected the shop is closed from the start.
+        let closing_permits = if total_customers == 0 { barbers } else { 0 };
+        let now = Instant::now();
+
         BarberShop {
-            semaphore: Arc::new(Semaphore::new(max_chairs as usize)),
+            customers: Semaphore::new(closing_permits as usize),
+            barbers,
             max_chairs,
-            customers_waiting: Arc::new(Mutex::new(0)),
-            remaining_customers: Arc::new(Mutex::new(total_customers)),
+            state: Mutex::new(ShopState {
+                vip_waiting: VecDeque::new(),
+                regular_waiting: VecDeque::new(),
+                remaining_customers: total_customers,
+                vip_stats: ClassStats::default(),
+                regular_stats: ClassStats::default(),
+                opened_at: now,
+                closed_at: if total_customers == 0 { Some(now) } else { None },
+                last_queue_change: now,
+                queue_area: 0.0,
+                busy_time: Duration::ZERO,
+            }),
         }
     }
-}
\ No newline at end of file
+}
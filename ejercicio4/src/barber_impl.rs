@@ -1,98 +1,376 @@
 /* The code above creates a  BarberShop  struct that represents a barber shop
-with a limited number of chairs and customers.
+with several barbers, a limited waiting room and a known number of customers.
 The  arrive_customer  method is used to handle the arrival of a customer,
-while the  cut_hair  method simulates the barber cutting hair.
-The  main  function creates a new  BarberShop  instance with 3 chairs and 8 total customers.
-It then spawns a barber thread to cut hair and multiple client threads to
-simulate customers arriving at the barber shop.
+while the  cut_hair  method simulates one barber cutting hair.
+The  run_barber_shop  function creates a new  BarberShop  instance from a  ShopConfig
+(2 barbers, 3 chairs and 20 customers by default). It then spawns one task per barber
+and one task per customer. Inter-arrival times, haircut times and patience are drawn
+from the configured distributions with a seeded generator, so a run is reproducible.
+Every duration is multiplied by the configured time scale, so a shop with hundreds of
+customers can be simulated in a few seconds.
 To run the code, execute the following command:
-cargo run */
+cargo run
+or, to skip the menu and pass the parameters directly:
+cargo run -- --problem 12 --customers 300 --barbers 4 --time-scale 0.01 */
 
 /* The output will show the customers arriving at the barber shop,
-waiting for their turn, and the barber cutting hair. The barber will sleep
-if there are no customers waiting, and the program will exit once all customers
-have received a haircut.*/
+waiting for their turn, and the barbers cutting hair. A barber sleeps on the
+`customers` semaphore while the waiting room is empty and is woken by the next
+customer that sits down. VIP customers are served before regular ones.
+A customer balks when the waiting room is full and reneges when it runs out of
+patience before a barber calls it. The shop closes once every customer has been
+served or has left, and a summary per customer class is printed, followed by the
+measured queue length, wait and utilization next to the M/M/c/K and M/M/c predictions.*/
 use std::sync::Arc;
-use tokio::time::Duration;
-use crate::barber::BarberShop;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, Instant};
+use crate::barber::{
+    BarberShop, ClassStats, CustomerClass, CustomerOutcome, ShopConfig, ShopState, WaitingCustomer,
+};
+use crate::queueing::{self, QueueMetrics};
 
 impl BarberShop {
     /// Handles the arrival of a customer.
     ///
+    /// The customer sits down if there is a free chair and waits until its own haircut
+    /// is finished. It balks if the waiting room is full and reneges if its patience runs
+    /// out before a barber calls it.
+    ///
     /// # Arguments
     ///
     /// * `id` - The ID of the arriving customer.
-    async fn arrive_customer(&self, id: u32) {
-        let mut customers_waiting = self.customers_waiting.lock().await;
+    /// * `class` - The class of the customer.
+    /// * `service_time` - How long the customer's haircut takes.
+    /// * `patience` - How long the customer is willing to wait, or `None` to wait forever.
+    ///
+    /// # Returns
+    ///
+    /// How the customer's visit ended.
+    pub(crate) async fn arrive_customer(
+        &self,
+        id: u32,
+        class: CustomerClass,
+        service_time: Duration,
+        patience: Option<Duration>,
+    ) -> CustomerOutcome {
+        let (done, mut haircut_finished) = oneshot::channel();
+
+        {
+            let mut state = self.state.lock().await;
 
-        if *customers_waiting < self.max_chairs {
-            let _ = self.semaphore.acquire().await.unwrap();
-            *customers_waiting += 1;
+            if state.waiting_len() as u32 >= self.max_chairs {
+                println!("No space for customer {}. Leaving the barber shop.", id);
+                state.stats_mut(class).balked += 1;
+                self.customer_gone(&mut state);
+                return CustomerOutcome::Balked;
+            }
+
+            state.record_queue_change();
+            state.queue_mut(class).push_back(WaitingCustomer {
+                id,
+                arrived_at: Instant::now(),
+                service_time,
+                done,
+            });
             println!(
-                "Customer {} is waiting. Total customers waiting: {}",
-                id, *customers_waiting
+                "Customer {} ({:?}) is waiting. Total customers waiting: {}",
+                id,
+                class,
+                state.waiting_len()
             );
+        }
+
+        // Signal the barbers that one more customer is waiting.
+        self.customers.add_permits(1);
 
-            if *customers_waiting == 1 {
-                println!("Customer {} wakes up the barber.", id);
+        // Wait in the chair until a barber finishes this customer's haircut.
+        let finished = match patience {
+            Some(patience) => tokio::time::timeout(patience, &mut haircut_finished).await.is_ok(),
+            None => (&mut haircut_finished).await.is_ok(),
+        };
+
+        if !finished {
+            let mut state = self.state.lock().await;
+            let position = state.queue_mut(class).iter().position(|customer| customer.id == id);
+
+            if let Some(position) = position {
+                state.record_queue_change();
+                state.queue_mut(class).remove(position);
+                println!("Customer {} ran out of patience. Leaving the barber shop.", id);
+                state.stats_mut(class).reneged += 1;
+                self.customer_gone(&mut state);
+                return CustomerOutcome::Reneged;
             }
-        } else {
-            println!("No space for customer {}. Leaving the barber shop.", id);
+
+            // A barber already called this customer, so it stays until the haircut is done.
+            drop(state);
+            let _ = haircut_finished.await;
         }
+
+        println!("Customer {} leaves with a fresh haircut.", id);
+        CustomerOutcome::Served
     }
 
-    /// Simulates the barber cutting hair.
-    async fn cut_hair(&self) {
+    /// Simulates one barber cutting hair.
+    ///
+    /// The barber blocks on the `customers` semaphore until a customer sits down, cuts
+    /// that customer's hair and notifies it. VIP customers are called first. The loop
+    /// ends once every customer has been served or has left the shop.
+    ///
+    /// # Arguments
+    ///
+    /// * `barber_id` - The ID of the barber.
+    pub(crate) async fn cut_hair(&self, barber_id: u32) {
         loop {
-            let mut customers_waiting = self.customers_waiting.lock().await;
+            let sleeping = {
+                let state = self.state.lock().await;
+                let sleeping = state.waiting_len() == 0 && state.remaining_customers > 0;
+                if sleeping {
+                    println!("Barber {} is sleeping, waiting for customers...", barber_id);
+                }
+                sleeping
+            };
 
-            if *customers_waiting == 0 {
-                println!("The barber is sleeping, waiting for customers...");
-                drop(customers_waiting);
-                tokio::time::sleep(Duration::from_secs(1)).await;
-                continue;
+            // Sleep until a customer (or the closing signal) arrives.
+            self.customers.acquire().await.unwrap().forget();
+            if sleeping {
+                println!("Barber {} wakes up.", barber_id);
             }
 
-            *customers_waiting -= 1;
+            let customer = {
+                let mut state = self.state.lock().await;
+                state.record_queue_change();
+
+                let next = match state.vip_waiting.pop_front() {
+                    Some(customer) => Some((customer, CustomerClass::Vip)),
+                    None => state
+                        .regular_waiting
+                        .pop_front()
+                        .map(|customer| (customer, CustomerClass::Regular)),
+                };
+
+                match next {
+                    Some((customer, class)) => {
+                        let stats = state.stats_mut(class);
+                        stats.served += 1;
+                        stats.total_wait += customer.arrived_at.elapsed();
+                        println!(
+                            "Barber {} is cutting hair of customer {} ({:?}). Customers waiting: {}",
+                            barber_id,
+                            customer.id,
+                            class,
+                            state.waiting_len()
+                        );
+                        customer
+                    }
+                    None if state.remaining_customers == 0 => {
+                        println!("Barber {} has finished cutting hair for all customers.", barber_id);
+                        break;
+                    }
+                    // The permit belonged to a customer that ran out of patience.
+                    None => continue,
+                }
+            };
+
+            tokio::time::sleep(customer.service_time).await;
             println!(
-                "The barber is cutting hair. Customers waiting: {}",
-                *customers_waiting
+                "Barber {} has finished cutting hair of customer {}.",
+                barber_id, customer.id
             );
-            drop(customers_waiting);
+            let _ = customer.done.send(());
 
-            tokio::time::sleep(Duration::from_secs(2)).await;
-            println!("The barber has finished cutting hair.");
+            let mut state = self.state.lock().await;
+            state.busy_time += customer.service_time;
+            self.customer_gone(&mut state);
+        }
+    }
 
-            let mut remaining_customers = self.remaining_customers.lock().await;
-            *remaining_customers -= 1;
+    /// Records that a customer has been served or has left the shop.
+    ///
+    /// When no customers remain, the closing time is recorded and one extra permit per
+    /// barber wakes every barber so they can close the shop.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The locked shop state.
+    fn customer_gone(&self, state: &mut ShopState) {
+        state.remaining_customers -= 1;
+        if state.remaining_customers == 0 {
+            state.closed_at = Some(Instant::now());
+            self.customers.add_permits(self.barbers as usize);
+        }
+    }
 
-            if *remaining_customers == 2 {
-                println!("The barber has finished cutting hair for all customers.");
-                break;
-            }
+    /// Prints the number of served, balked and reneged customers and the average
+    /// wait of the served customers per customer class, followed by the measured
+    /// queue figures next to the analytic predictions for the same parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration the shop was run with.
+    async fn print_report(&self, config: &ShopConfig) {
+        let state = self.state.lock().await;
 
-            self.semaphore.add_permits(1);
+        println!("===== Barber shop summary =====");
+        println!(
+            "{:<8} {:>7} {:>7} {:>8} {:>10}",
+            "Class", "Served", "Balked", "Reneged", "Avg wait"
+        );
+        for (name, stats) in [("Regular", &state.regular_stats), ("VIP", &state.vip_stats)] {
+            println!(
+                "{:<8} {:>7} {:>7} {:>8} {:>9.2}s",
+                name,
+                stats.served,
+                stats.balked,
+                stats.reneged,
+                average_wait(stats).as_secs_f64()
+            );
+        }
+
+        if config.interarrival.mean().is_zero() {
+            // Every customer arrives at once: there is no arrival rate to compare against.
+            println!("Note: customers arrive in a single burst, so the analytic models do not apply.");
+            return;
+        }
+
+        let measured = measured_metrics(&state, self.barbers);
+        let lambda = rate(config.interarrival.mean());
+        let mu = rate(config.service.mean());
+        let capacity = self.barbers + self.max_chairs;
+        let mmck = queueing::mmck(lambda, mu, self.barbers, capacity);
+        let mmc = queueing::mmc(lambda, mu, self.barbers);
+
+        println!("===== Queueing theory comparison =====");
+        println!(
+            "lambda = {:.3}/s, mu = {:.3}/s, c = {}, K = {}",
+            lambda, mu, self.barbers, capacity
+        );
+        println!(
+            "{:<18} {:>10} {:>10} {:>10}",
+            "Metric", "Measured", "M/M/c/K", "M/M/c"
+        );
+        let rows = [
+            ("Mean queue length", measured.mean_queue_length, mmck.mean_queue_length, mmc.map(|m| m.mean_queue_length)),
+            ("Mean wait (s)", measured.mean_wait, mmck.mean_wait, mmc.map(|m| m.mean_wait)),
+            ("Utilization", measured.utilization, mmck.utilization, mmc.map(|m| m.utilization)),
+            ("Balk probability", measured.blocking, mmck.blocking, mmc.map(|m| m.blocking)),
+        ];
+        for (name, measured_value, mmck_value, mmc_value) in rows {
+            let mmc_value = match mmc_value {
+                Some(value) => format!("{:.3}", value),
+                None => "unstable".to_string(),
+            };
+            println!(
+                "{:<18} {:>10.3} {:>10.3} {:>10}",
+                name, measured_value, mmck_value, mmc_value
+            );
+        }
+
+        if !config.interarrival.is_exponential() || !config.service.is_exponential() {
+            println!("Note: the analytic models assume exponential arrivals and haircuts.");
+        }
+        if config.patience.is_some() {
+            println!("Note: reneging customers are not part of the analytic models.");
         }
     }
 }
 
-pub async fn run_barber_shop(){
-    let total_customers = 8; // Simulate with more customers to force accumulation
-    let shop = Arc::new(BarberShop::new(3, total_customers)); // 3 chairs
+/// Computes the queue figures measured during a run.
+///
+/// # Arguments
+///
+/// * `state` - The shop state after the run.
+/// * `barbers` - The number of barbers.
+fn measured_metrics(state: &ShopState, barbers: u32) -> QueueMetrics {
+    let closed_at = state.closed_at.unwrap_or_else(Instant::now);
+    let elapsed = (closed_at - state.opened_at).as_secs_f64();
+    let served = state.regular_stats.served + state.vip_stats.served;
+    let balked = state.regular_stats.balked + state.vip_stats.balked;
+    let reneged = state.regular_stats.reneged + state.vip_stats.reneged;
+    let arrivals = served + balked + reneged;
+    let total_wait = state.regular_stats.total_wait + state.vip_stats.total_wait;
 
-    let barber_shop = Arc::clone(&shop);
-    let barber_thread = tokio::spawn(async move {
-        barber_shop.cut_hair().await;
-    });
+    QueueMetrics {
+        mean_queue_length: if elapsed > 0.0 { state.queue_area / elapsed } else { 0.0 },
+        mean_wait: if served > 0 { total_wait.as_secs_f64() / served as f64 } else { 0.0 },
+        utilization: if elapsed > 0.0 && barbers > 0 {
+            state.busy_time.as_secs_f64() / (elapsed * barbers as f64)
+        } else {
+            0.0
+        },
+        blocking: if arrivals > 0 { balked as f64 / arrivals as f64 } else { 0.0 },
+    }
+}
+
+/// Converts the mean of a duration into a rate per second.
+///
+/// # Arguments
+///
+/// * `mean` - The mean duration between two events.
+fn rate(mean: Duration) -> f64 {
+    if mean.is_zero() {
+        f64::INFINITY
+    } else {
+        1.0 / mean.as_secs_f64()
+    }
+}
+
+/// Returns the average wait of the served customers of a class.
+///
+/// # Arguments
+///
+/// * `stats` - The counters of the class.
+fn average_wait(stats: &ClassStats) -> Duration {
+    if stats.served == 0 {
+        Duration::ZERO
+    } else {
+        stats.total_wait / stats.served
+    }
+}
+
+/// Runs the barber shop simulation with the given configuration and prints its report.
+///
+/// # Arguments
+///
+/// * `config` - The barbers, chairs, customers and distributions of the run.
+pub async fn run_barber_shop(config: ShopConfig) {
+    if config.time_scale != 1.0 {
+        println!("Time is scaled by {}; the report shows scaled times.", config.time_scale);
+    }
+    let config = config.compressed();
+    let shop = Arc::new(BarberShop::new(config.barbers, config.chairs, config.customers));
+    let mut rng = StdRng::seed_from_u64(config.seed);
+
+    let mut barber_threads = vec![];
+
+    for barber_id in 1..=config.barbers {
+        let barber_shop = Arc::clone(&shop);
+        barber_threads.push(tokio::spawn(async move {
+            barber_shop.cut_hair(barber_id).await;
+        }));
+    }
 
     let mut client_threads = vec![];
+    let mut arrival = Duration::ZERO;
+
+    for i in 1..=config.customers {
+        // Every random draw happens here, in customer order, so the seed fixes the whole run.
+        arrival += config.interarrival.sample(&mut rng);
+        let class = if rng.gen_bool(config.vip_probability) {
+            CustomerClass::Vip
+        } else {
+            CustomerClass::Regular
+        };
+        let service_time = config.service.sample(&mut rng);
+        let patience = config.patience.map(|patience| patience.sample(&mut rng));
 
-    for i in 1..=total_customers {
         let shop = Arc::clone(&shop);
         let client_thread = tokio::spawn(async move {
-            tokio::time::sleep(Duration::from_secs(i as u64)).await; // Staggered arrivals
-            println!("Customer {} has arrived.", i);
-            shop.arrive_customer(i).await;
+            tokio::time::sleep(arrival).await;
+            println!("Customer {} ({:?}) has arrived.", i, class);
+            shop.arrive_customer(i, class, service_time, patience).await;
         });
         client_threads.push(client_thread);
     }
@@ -101,5 +379,9 @@ pub async fn run_barber_shop(){
         client.await.unwrap();
     }
 
-    barber_thread.await.unwrap();
-}
\ No newline at end of file
+    for barber in barber_threads {
+        barber.await.unwrap();
+    }
+
+    shop.print_report(&config).await;
+}
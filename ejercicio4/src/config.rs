@@ -0,0 +1,387 @@
+/*
+The `Settings` struct gathers the parameters of every scenario of this exercise: the
+`ShopConfig` of Problem 12 and the `ReadersWritersConfig` of Problem 13. They start with
+the defaults of the original exercise and can be changed from the command line, from a
+scenario file or from the menu prompts. The three paths go through `Settings::set`, so a
+parameter is named and validated the same way everywhere.
+
+Command-line flags are written as `--key value` or `--key=value`. `--scenario FILE`
+reads a scenario file with one `key = value` per line, where blank lines and lines
+starting with `#` are ignored. Values are applied in the order they are given, so a flag
+after `--scenario` overrides the file. `--problem 12`, `--problem 13` or
+`--problem 13-policies` runs that scenario directly instead of showing the menu, and
+`--help` lists every key.
+
+Example scenario file:
+# A busy barber shop, 100 times faster than real time
+barbers = 4
+chairs = 10
+customers = 400
+arrival = exp:0.5
+haircut = uni:1:2.5
+time-scale = 0.01
+*/
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+use crate::barber::ShopConfig;
+use crate::read_write_lock::ReadersWritersConfig;
+
+/// Scenario that can be run without going through the menu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Problem {
+    BarberShop,     // Problem 12
+    ReadersWriters, // Problem 13
+    Policies,       // Problem 13, comparing the lock policies
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Problem::BarberShop => "12",
+            Problem::ReadersWriters => "13",
+            Problem::Policies => "13-policies",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Problem {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            "12" => Ok(Problem::BarberShop),
+            "13" => Ok(Problem::ReadersWriters),
+            "13-policies" => Ok(Problem::Policies),
+            other => Err(format!("problema '{}' no reconocido (use 12, 13 o 13-policies)", other)),
+        }
+    }
+}
+
+/// Keys asked for, in order, before running Problem 12.
+pub const SHOP_KEYS: [&str; 9] = [
+    "barbers",
+    "chairs",
+    "customers",
+    "arrival",
+    "haircut",
+    "patience",
+    "vip-probability",
+    "seed",
+    "time-scale",
+];
+
+/// Keys asked for, in order, before running Problem 13.
+pub const READERS_WRITERS_KEYS: [&str; 9] = [
+    "readers",
+    "writers",
+    "iterations",
+    "read-time",
+    "write-time",
+    "start-gap",
+    "policy",
+    "seed",
+    "time-scale",
+];
+
+/// Every key with the text shown for it in the prompts and in `--help`.
+const DESCRIPTIONS: [(&str, &str); 17] = [
+    ("barbers", "Número de barberos"),
+    ("chairs", "Sillas en la sala de espera"),
+    ("customers", "Número de clientes"),
+    ("arrival", "Distribución de llegadas"),
+    ("haircut", "Distribución del corte de pelo"),
+    ("patience", "Paciencia de los clientes (none o distribución)"),
+    ("vip-probability", "Probabilidad de cliente VIP"),
+    ("readers", "Número de lectores"),
+    ("writers", "Número de escritores"),
+    ("iterations", "Accesos de cada lector y escritor"),
+    ("read-time", "Distribución del tiempo de lectura"),
+    ("write-time", "Distribución del tiempo de escritura"),
+    ("start-gap", "Distribución del tiempo entre arranques"),
+    ("policy", "Política del cerrojo"),
+    ("seed", "Semilla"),
+    ("time-scale", "Escala de tiempo (menor que 1 comprime)"),
+    ("problem", "Problema a ejecutar sin menú (12, 13 o 13-policies)"),
+];
+
+/// Parameters of every scenario, plus the scenario to run directly, if any.
+#[derive(Clone, Debug, Default)]
+pub struct Settings {
+    pub shop: ShopConfig,                       // Parameters of Problem 12
+    pub readers_writers: ReadersWritersConfig,  // Parameters of Problem 13
+    pub problem: Option<Problem>,               // Scenario to run without showing the menu
+}
+
+impl Settings {
+    /// Builds the settings from the command-line arguments, without the program name.
+    /// Returns `None` when `--help` was asked for.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The command-line arguments.
+    pub fn from_args<I>(args: I) -> Result<Option<Settings>, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut settings = Settings::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                return Ok(None);
+            }
+            let flag = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("argumento '{}' inesperado, las opciones empiezan por --", arg))?;
+            let (key, value) = match flag.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                None => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("falta el valor de --{}", flag))?;
+                    (flag.to_string(), value)
+                }
+            };
+
+            if key == "scenario" {
+                settings.load_scenario(&value)?;
+            } else {
+                settings.set(&key, &value).map_err(|error| format!("--{}: {}", key, error))?;
+            }
+        }
+
+        Ok(Some(settings))
+    }
+
+    /// Applies every `key = value` line of a scenario file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the scenario file.
+    pub fn load_scenario(&mut self, path: &str) -> Result<(), String> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("no se pudo leer el escenario '{}': {}", path, error))?;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("{}:{}: se esperaba 'clave = valor'", path, number + 1))?;
+            self.set(key.trim(), value.trim())
+                .map_err(|error| format!("{}:{}: {}: {}", path, number + 1, key.trim(), error))?;
+        }
+
+        Ok(())
+    }
+
+    /// Changes one parameter. `seed` and `time-scale` apply to every scenario.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the parameter.
+    /// * `value` - The new value, as text.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let shop = &mut self.shop;
+        let readers_writers = &mut self.readers_writers;
+
+        match key {
+            "barbers" => shop.barbers = parse(value)?,
+            "chairs" => shop.chairs = parse(value)?,
+            "customers" => shop.customers = parse(value)?,
+            "arrival" => shop.interarrival = parse(value)?,
+            "haircut" => shop.service = parse(value)?,
+            "patience" => {
+                shop.patience = match value.trim() {
+                    "none" => None,
+                    value => Some(parse(value)?),
+                }
+            }
+            "vip-probability" => shop.vip_probability = parse(value)?,
+            "readers" => readers_writers.readers = parse(value)?,
+            "writers" => readers_writers.writers = parse(value)?,
+            "iterations" => readers_writers.iterations = parse(value)?,
+            "read-time" => readers_writers.read_time = parse(value)?,
+            "write-time" => readers_writers.write_time = parse(value)?,
+            "start-gap" => readers_writers.start_gap = parse(value)?,
+            "policy" => readers_writers.policy = parse(value)?,
+            "seed" => {
+                let seed = parse(value)?;
+                shop.seed = seed;
+                readers_writers.seed = seed;
+            }
+            "time-scale" => {
+                let scale = parse(value)?;
+                shop.time_scale = scale;
+                readers_writers.time_scale = scale;
+            }
+            "problem" => self.problem = Some(parse(value)?),
+            _ => return Err(format!("opción '{}' desconocida (use --help para ver la lista)", key)),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the current value of a parameter, written as `set` accepts it.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The name of the parameter.
+    pub fn get(&self, key: &str) -> String {
+        let shop = &self.shop;
+        let readers_writers = &self.readers_writers;
+
+        match key {
+            "barbers" => shop.barbers.to_string(),
+            "chairs" => shop.chairs.to_string(),
+            "customers" => shop.customers.to_string(),
+            "arrival" => shop.interarrival.to_string(),
+            "haircut" => shop.service.to_string(),
+            "patience" => shop.patience.map_or("none".to_string(), |patience| patience.to_string()),
+            "vip-probability" => shop.vip_probability.to_string(),
+            "readers" => readers_writers.readers.to_string(),
+            "writers" => readers_writers.writers.to_string(),
+            "iterations" => readers_writers.iterations.to_string(),
+            "read-time" => readers_writers.read_time.to_string(),
+            "write-time" => readers_writers.write_time.to_string(),
+            "start-gap" => readers_writers.start_gap.to_string(),
+            "policy" => readers_writers.policy.to_string(),
+            "seed" => shop.seed.to_string(),
+            "time-scale" => shop.time_scale.to_string(),
+            "problem" => self.problem.map_or("none".to_string(), |problem| problem.to_string()),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Returns the text shown for a key in the prompts and in `--help`.
+///
+/// # Arguments
+///
+/// * `key` - The name of the parameter.
+pub fn description(key: &str) -> &'static str {
+    DESCRIPTIONS
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map_or("", |(_, description)| description)
+}
+
+/// Returns the help text listing every flag with its default value.
+pub fn usage() -> String {
+    let defaults = Settings::default();
+    let mut text = String::from(
+        "Uso: ejercicio4 [--scenario ARCHIVO] [--CLAVE VALOR]...\n\n\
+         Las distribuciones se escriben det:S, exp:MEDIA o uni:MIN:MAX, en segundos.\n\
+         Un archivo de escenario contiene una línea 'clave = valor' por opción.\n\n",
+    );
+    for (key, description) in DESCRIPTIONS {
+        text.push_str(&format!("  --{:<16} {} [{}]\n", key, description, defaults.get(key)));
+    }
+    text
+}
+
+/// Parses a value, describing what was expected when it fails.
+///
+/// # Arguments
+///
+/// * `value` - The text to parse.
+fn parse<T>(value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|error| format!("valor '{}' inválido: {}", value.trim(), error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_write_lock::LockPolicy;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn flags_override_defaults() {
+        let settings = Settings::from_args(args(
+            "--customers 300 --barbers=4 --policy fifo --time-scale 0.01 --problem 12",
+        ))
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(settings.shop.customers, 300);
+        assert_eq!(settings.shop.barbers, 4);
+        assert_eq!(settings.readers_writers.policy, LockPolicy::Fifo);
+        assert_eq!(settings.shop.time_scale, 0.01);
+        assert_eq!(settings.readers_writers.time_scale, 0.01);
+        assert_eq!(settings.problem, Some(Problem::BarberShop));
+    }
+
+    #[test]
+    fn bad_flags_name_the_offending_option() {
+        let error = Settings::from_args(args("--readers many")).unwrap_err();
+        assert!(error.starts_with("--readers:"), "{}", error);
+
+        let error = Settings::from_args(args("--arrival")).unwrap_err();
+        assert!(error.contains("falta el valor"), "{}", error);
+
+        let error = Settings::from_args(args("--colour blue")).unwrap_err();
+        assert!(error.contains("desconocida"), "{}", error);
+
+        assert!(Settings::from_args(args("--help")).unwrap().is_none());
+    }
+
+    #[test]
+    fn scenario_file_is_applied_and_errors_carry_the_line() {
+        let dir = std::env::temp_dir();
+        let good = dir.join(format!("ejercicio4-good-{}.txt", std::process::id()));
+        let bad = dir.join(format!("ejercicio4-bad-{}.txt", std::process::id()));
+        fs::write(&good, "# comment\n\nreaders = 200\nread-time = exp:0.5\npatience = det:3\n").unwrap();
+        fs::write(&bad, "writers = 5\nwrite-time = sometimes\n").unwrap();
+
+        let mut settings = Settings::default();
+        settings.load_scenario(good.to_str().unwrap()).unwrap();
+        assert_eq!(settings.readers_writers.readers, 200);
+        assert_eq!(settings.get("read-time"), "exp:0.5");
+        assert_eq!(settings.get("patience"), "det:3");
+
+        let error = settings.load_scenario(bad.to_str().unwrap()).unwrap_err();
+        assert!(error.contains(":2: write-time:"), "{}", error);
+
+        fs::remove_file(good).unwrap();
+        fs::remove_file(bad).unwrap();
+    }
+
+    #[test]
+    fn validation_rejects_impossible_runs() {
+        let mut settings = Settings::default();
+        assert!(settings.shop.validate().is_ok());
+        assert!(settings.readers_writers.validate().is_ok());
+
+        settings.set("barbers", "0").unwrap();
+        settings.set("iterations", "0").unwrap();
+        assert!(settings.shop.validate().is_err());
+        assert!(settings.readers_writers.validate().is_err());
+
+        settings.set("barbers", "1").unwrap();
+        settings.set("vip-probability", "1.5").unwrap();
+        assert!(settings.shop.validate().is_err());
+    }
+
+    #[test]
+    fn simultaneous_arrivals_are_a_valid_shop() {
+        let mut settings = Settings::default();
+        settings.set("arrival", "det:0").unwrap();
+        assert!(settings.shop.validate().is_ok());
+        settings.set("haircut", "det:0").unwrap();
+        assert!(settings.shop.validate().is_err());
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use std::str::FromStr;
+use rand::Rng;
+use tokio::time::Duration;
+
+/// Probability distribution used to draw simulated durations.
+#[derive(Clone, Copy, Debug)]
+pub enum TimeDistribution {
+    /// Always returns the same duration.
+    Deterministic(Duration),
+    /// Exponentially distributed duration with the given mean.
+    Exponential { mean: Duration },
+    /// Uniformly distributed duration between `min` and `max` (inclusive).
+    Uniform { min: Duration, max: Duration },
+}
+
+impl TimeDistribution {
+    /// Draws a duration from the distribution.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng` - The random number generator to draw from.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Duration {
+        match *self {
+            TimeDistribution::Deterministic(duration) => duration,
+            TimeDistribution::Exponential { mean } => {
+                // Inverse transform sampling: -mean * ln(1 - u), with u in [0, 1).
+                let u: f64 = rng.gen();
+                mean.mul_f64(-(1.0 - u).ln())
+            }
+            TimeDistribution::Uniform { min, max } => {
+                if min >= max {
+                    min
+                } else {
+                    rng.gen_range(min..=max)
+                }
+            }
+        }
+    }
+
+    /// Returns the mean of the distribution.
+    pub fn mean(&self) -> Duration {
+        match *self {
+            TimeDistribution::Deterministic(duration) => duration,
+            TimeDistribution::Exponential { mean } => mean,
+            TimeDistribution::Uniform { min, max } => (min + max) / 2,
+        }
+    }
+
+    /// Returns the same distribution with every duration multiplied by `factor`.
+    /// Used to compress (or stretch) the time of a whole simulation at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - The scale applied to every duration. Must be positive.
+    pub fn scaled(&self, factor: f64) -> Self {
+        match *self {
+            TimeDistribution::Deterministic(duration) => TimeDistribution::Deterministic(duration.mul_f64(factor)),
+            TimeDistribution::Exponential { mean } => TimeDistribution::Exponential { mean: mean.mul_f64(factor) },
+            TimeDistribution::Uniform { min, max } => TimeDistribution::Uniform {
+                min: min.mul_f64(factor),
+                max: max.mul_f64(factor),
+            },
+        }
+    }
+
+    /// Returns `true` if the distribution is exponential (memoryless).
+    pub fn is_exponential(&self) -> bool {
+        matches!(self, TimeDistribution::Exponential { .. })
+    }
+}
+
+impl fmt::Display for TimeDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeDistribution::Deterministic(duration) => write!(f, "det:{}", duration.as_secs_f64()),
+            TimeDistribution::Exponential { mean } => write!(f, "exp:{}", mean.as_secs_f64()),
+            TimeDistribution::Uniform { min, max } => {
+                write!(f, "uni:{}:{}", min.as_secs_f64(), max.as_secs_f64())
+            }
+        }
+    }
+}
+
+impl FromStr for TimeDistribution {
+    type Err = String;
+
+    /// Parses a distribution written as `det:SECONDS`, `exp:MEAN_SECONDS` or
+    /// `uni:MIN_SECONDS:MAX_SECONDS`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to parse.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = text.trim().split(':').collect();
+        let seconds = |value: &str| -> Result<Duration, String> {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                .ok_or_else(|| format!("'{}' no es una cantidad de segundos válida", value))
+        };
+
+        match parts.as_slice() {
+            ["det", value] => Ok(TimeDistribution::Deterministic(seconds(value)?)),
+            ["exp", mean] => Ok(TimeDistribution::Exponential { mean: seconds(mean)? }),
+            ["uni", min, max] => {
+                let (min, max) = (seconds(min)?, seconds(max)?);
+                if min > max {
+                    return Err(format!("el mínimo {}s es mayor que el máximo {}s", min.as_secs_f64(), max.as_secs_f64()));
+                }
+                Ok(TimeDistribution::Uniform { min, max })
+            }
+            _ => Err(format!(
+                "distribución '{}' no reconocida (use det:S, exp:MEDIA o uni:MIN:MAX)",
+                text.trim()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn parses_each_distribution() {
+        assert!(matches!(
+            "det:0.5".parse(),
+            Ok(TimeDistribution::Deterministic(duration)) if duration == Duration::from_millis(500)
+        ));
+        assert!(matches!(
+            " exp:2 ".parse(),
+            Ok(TimeDistribution::Exponential { mean }) if mean == Duration::from_secs(2)
+        ));
+        assert!(matches!(
+            "uni:1:3".parse(),
+            Ok(TimeDistribution::Uniform { min, max }) if min == Duration::from_secs(1) && max == Duration::from_secs(3)
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_distributions() {
+        for text in ["", "det", "det:", "det:-1", "exp:x", "uni:1", "uni:3:1", "uni:1-3", "gauss:1", "det:1:2"] {
+            assert!(text.parse::<TimeDistribution>().is_err(), "{:?} was accepted", text);
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for text in ["det:0.25", "exp:1.5", "uni:0.5:2"] {
+            let distribution: TimeDistribution = text.parse().unwrap();
+            assert_eq!(distribution.to_string(), text);
+        }
+    }
+
+    #[test]
+    fn exponential_samples_converge_to_the_mean() {
+        let distribution = TimeDistribution::Exponential { mean: Duration::from_secs(2) };
+        let mut rng = StdRng::seed_from_u64(7);
+        let samples = 100_000;
+        let total: f64 = (0..samples).map(|_| distribution.sample(&mut rng).as_secs_f64()).sum();
+        assert!((total / samples as f64 - 2.0).abs() < 0.05, "mean {}", total / samples as f64);
+    }
+
+    #[test]
+    fn uniform_samples_stay_in_range() {
+        let distribution = TimeDistribution::Uniform { min: Duration::from_secs(1), max: Duration::from_secs(3) };
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..1000 {
+            let sample = distribution.sample(&mut rng);
+            assert!(sample >= Duration::from_secs(1) && sample <= Duration::from_secs(3));
+        }
+        assert_eq!(distribution.mean(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn scaling_scales_the_mean() {
+        for text in ["det:2", "exp:2", "uni:1:3"] {
+            let distribution: TimeDistribution = text.parse().unwrap();
+            assert_eq!(distribution.scaled(0.5).mean(), Duration::from_secs(1));
+        }
+    }
+}
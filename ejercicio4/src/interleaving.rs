@@ -0,0 +1,460 @@
+/*
+Controllable scheduler used by the tests to explore task interleavings systematically.
+
+A scenario is a set of tasks (futures) plus a final check. Instead of handing the tasks
+to tokio, the explorer polls them itself, one at a time, inside a single-threaded tokio
+runtime whose clock is paused. Every time more than one task is ready to run, the
+scheduler makes a decision, and the list of decisions taken in a run is its schedule.
+The explorer runs the scenario again and again, walking the tree of decisions depth
+first, until every schedule has been tried or the configured limit is reached.
+
+The real primitives (`tokio::sync`, `tokio::time`, the locks) run unchanged: they only
+see wakers, so the scheduler decides who goes next. Sleeps and timeouts complete when
+the paused clock advances, which only happens once no task can make progress, so timing
+is deterministic. If tasks are still blocked and no timer is pending, the run is
+reported as a deadlock.
+
+A failing run is reported with its schedule, for example `[0, 2, 1]`. Passing that
+schedule to `replay` runs exactly the same interleaving again.
+
+Why tokio's paused clock and not an explicit clock like the bear colony's `SimClock`: the
+barber shop waits with `tokio::time` itself (the haircut is a `sleep`, an impatient
+customer's patience is a `timeout`), and the point of the explorer is to run that code
+unchanged. Only tokio's paused clock can drive those timers without threading a clock
+through the shop, so `test-util` is a dev-dependency and `start_paused` is used here and
+nowhere else; the binary always runs on the real clock. The readers/writers lock never
+waits on a timer, so for it the paused clock only backs the deadlock timeout.
+
+What the exploration covers: the scheduler can only switch tasks where a task returns
+`Pending`, that is, where it blocks on a lock, semaphore, notification or timer, or at
+a `yield_point`. Production code has no `yield_point`s; the scenarios add them inside the
+critical sections they want to interleave. Code between two such points always runs as a
+unit, so races that need a thread to be preempted in the middle of it (for example between
+two atomic operations on a multi-threaded runtime) are not explored.
+*/
+
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use tokio::time::Duration;
+
+/// A task of a scenario.
+pub type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+/// Simulated time after which blocked tasks are considered deadlocked.
+const DEADLOCK_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Tasks to interleave and the check to run once all of them have finished.
+pub struct Scenario {
+    pub tasks: Vec<Task>,
+    pub check: Box<dyn FnOnce()>,
+}
+
+impl Scenario {
+    /// Creates a scenario from its tasks, with no final check.
+    ///
+    /// # Arguments
+    ///
+    /// * `tasks` - The tasks to interleave.
+    pub fn new(tasks: Vec<Task>) -> Self {
+        Scenario { tasks, check: Box::new(|| {}) }
+    }
+
+    /// Sets the check to run after every task has finished. It should panic on a violation.
+    ///
+    /// # Arguments
+    ///
+    /// * `check` - The final check.
+    pub fn with_check(mut self, check: impl FnOnce() + 'static) -> Self {
+        self.check = Box::new(check);
+        self
+    }
+}
+
+/// A schedule under which a scenario failed.
+#[derive(Debug)]
+pub struct Failure {
+    pub schedule: Vec<usize>, // Decisions taken, as positions among the tasks ready at that point
+    pub message: String,      // Panic message, or a description of the deadlock
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed under schedule {:?}: {}\nreplay with interleaving::replay(scenario, &{:?})",
+            self.schedule, self.message, self.schedule
+        )
+    }
+}
+
+/// Summary of an exploration that found no failure.
+#[derive(Debug)]
+pub struct Exploration {
+    pub schedules: usize, // Number of schedules run
+    pub exhaustive: bool, // Whether every schedule was tried
+}
+
+/// Waker of one task: marks the task as ready and wakes the scheduler.
+struct TaskWaker {
+    ready: AtomicBool,
+    scheduler: Arc<Mutex<Option<Waker>>>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.ready.store(true, Ordering::SeqCst);
+        if let Some(scheduler) = self.scheduler.lock().unwrap().as_ref() {
+            scheduler.wake_by_ref();
+        }
+    }
+}
+
+/// Gives the scheduler a chance to run another task at this point.
+pub async fn yield_point() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Runs every schedule of a scenario, depth first, until one fails or `max_schedules`
+/// have been run.
+///
+/// # Arguments
+///
+/// * `scenario` - Builds a fresh scenario for each run.
+/// * `max_schedules` - Upper bound on the number of runs.
+pub fn explore<F>(mut scenario: F, max_schedules: usize) -> Result<Exploration, Failure>
+where
+    F: FnMut() -> Scenario,
+{
+    let mut prefix = Vec::new();
+    let mut schedules = 0;
+
+    loop {
+        let (mut decisions, result) = run_once(scenario(), &prefix);
+        schedules += 1;
+
+        if let Err(message) = result {
+            return Err(Failure {
+                schedule: decisions.iter().map(|&(choice, _)| choice).collect(),
+                message,
+            });
+        }
+
+        // Move to the next unexplored branch: the deepest decision with an untried option.
+        while let Some((choice, options)) = decisions.pop() {
+            if choice + 1 < options {
+                decisions.push((choice + 1, options));
+                break;
+            }
+        }
+
+        if decisions.is_empty() {
+            return Ok(Exploration { schedules, exhaustive: true });
+        }
+        if schedules >= max_schedules {
+            return Ok(Exploration { schedules, exhaustive: false });
+        }
+        prefix = decisions.iter().map(|&(choice, _)| choice).collect();
+    }
+}
+
+/// Runs a scenario once under the given schedule.
+///
+/// # Arguments
+///
+/// * `scenario` - The scenario to run.
+/// * `schedule` - The decisions to take, as reported by a `Failure`.
+pub fn replay(scenario: Scenario, schedule: &[usize]) -> Result<(), String> {
+    run_once(scenario, schedule).1
+}
+
+/// Runs a scenario once. Decisions follow `prefix` and then always pick the first ready task.
+///
+/// # Arguments
+///
+/// * `scenario` - The scenario to run.
+/// * `prefix` - The first decisions to take.
+///
+/// # Returns
+///
+/// Every decision taken, as `(choice, number of options)`, and the outcome of the run.
+fn run_once(scenario: Scenario, prefix: &[usize]) -> (Vec<(usize, usize)>, Result<(), String>) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()
+        .unwrap();
+
+    let Scenario { tasks, check } = scenario;
+    let mut tasks: Vec<Option<Task>> = tasks.into_iter().map(Some).collect();
+    let scheduler = Arc::new(Mutex::new(None));
+    let wakers: Vec<Arc<TaskWaker>> = tasks
+        .iter()
+        .map(|_| {
+            Arc::new(TaskWaker {
+                ready: AtomicBool::new(true),
+                scheduler: Arc::clone(&scheduler),
+            })
+        })
+        .collect();
+    let mut decisions = Vec::new();
+
+    let result = runtime.block_on(async {
+        let run = poll_fn(|cx| {
+            *scheduler.lock().unwrap() = Some(cx.waker().clone());
+
+            loop {
+                let ready: Vec<usize> = (0..tasks.len())
+                    .filter(|&index| {
+                        tasks[index].is_some() && wakers[index].ready.load(Ordering::SeqCst)
+                    })
+                    .collect();
+
+                if ready.is_empty() {
+                    return if tasks.iter().all(Option::is_none) {
+                        Poll::Ready(Ok(()))
+                    } else {
+                        Poll::Pending
+                    };
+                }
+
+                let choice = if ready.len() > 1 {
+                    let choice = prefix.get(decisions.len()).copied().unwrap_or(0).min(ready.len() - 1);
+                    decisions.push((choice, ready.len()));
+                    choice
+                } else {
+                    0
+                };
+
+                let index = ready[choice];
+                wakers[index].ready.store(false, Ordering::SeqCst);
+                let waker = Waker::from(Arc::clone(&wakers[index]));
+                let mut task_cx = Context::from_waker(&waker);
+                let task = tasks[index].as_mut().unwrap();
+
+                match catch_unwind(AssertUnwindSafe(|| task.as_mut().poll(&mut task_cx))) {
+                    Ok(Poll::Ready(())) => tasks[index] = None,
+                    Ok(Poll::Pending) => {}
+                    Err(panic) => return Poll::Ready(Err(panic_message(panic))),
+                }
+            }
+        });
+
+        match tokio::time::timeout(DEADLOCK_TIMEOUT, run).await {
+            Ok(result) => result,
+            Err(_) => Err("deadlock: tasks are blocked and no timer is pending".to_string()),
+        }
+    });
+
+    let result = result.and_then(|()| catch_unwind(AssertUnwindSafe(check)).map_err(panic_message));
+    (decisions, result)
+}
+
+/// Extracts the message of a panic payload.
+///
+/// # Arguments
+///
+/// * `panic` - The payload returned by `catch_unwind`.
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "task panicked".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use crate::barber::{BarberShop, CustomerClass, CustomerOutcome};
+    use crate::read_write_lock::{LockPolicy, ReadWriteLock};
+
+    /// Who is inside the critical section of a lock right now.
+    #[derive(Default)]
+    struct Occupancy {
+        readers: u32,
+        writers: u32,
+    }
+
+    #[test]
+    fn explorer_finds_a_lost_update_and_replays_it() {
+        // Two tasks increment a shared counter with a read-yield-write sequence and no lock.
+        let scenario = || {
+            let counter = Rc::new(RefCell::new(0));
+            let mut tasks: Vec<Task> = vec![];
+            for _ in 0..2 {
+                let counter = Rc::clone(&counter);
+                tasks.push(Box::pin(async move {
+                    let value = *counter.borrow();
+                    yield_point().await;
+                    *counter.borrow_mut() = value + 1;
+                }));
+            }
+            Scenario::new(tasks).with_check(move || assert_eq!(*counter.borrow(), 2, "lost update"))
+        };
+
+        let failure = explore(scenario, 100).unwrap_err();
+        assert!(failure.message.contains("lost update"));
+        assert!(replay(scenario(), &failure.schedule).is_err());
+    }
+
+    #[test]
+    fn explorer_reports_deadlocks() {
+        let scenario = || {
+            let lock = Rc::new(ReadWriteLock::with_policy(0, LockPolicy::ReaderPreferring));
+            let task_lock = Rc::clone(&lock);
+            let task: Task = Box::pin(async move {
+                let _reader = task_lock.read().await;
+                let _writer = task_lock.write().await;
+            });
+            Scenario::new(vec![task])
+        };
+
+        let failure = explore(scenario, 10).unwrap_err();
+        assert!(failure.message.contains("deadlock"), "{}", failure);
+    }
+
+    /// Readers, a writer and an upgrading reader that record who is inside the lock.
+    fn read_write_scenario(policy: LockPolicy) -> Scenario {
+        let lock = Rc::new(ReadWriteLock::with_policy(0u32, policy));
+        let occupancy = Rc::new(RefCell::new(Occupancy::default()));
+        let mut tasks: Vec<Task> = vec![];
+
+        for _ in 0..2 {
+            let (lock, occupancy) = (Rc::clone(&lock), Rc::clone(&occupancy));
+            tasks.push(Box::pin(async move {
+                let value = lock.read().await;
+                occupancy.borrow_mut().readers += 1;
+                assert_eq!(occupancy.borrow().writers, 0, "reader alongside a writer");
+                yield_point().await;
+                occupancy.borrow_mut().readers -= 1;
+                drop(value);
+            }));
+        }
+
+        let (writer_lock, writer_occupancy) = (Rc::clone(&lock), Rc::clone(&occupancy));
+        tasks.push(Box::pin(async move {
+            let mut value = writer_lock.write().await;
+            {
+                let mut occupancy = writer_occupancy.borrow_mut();
+                assert_eq!((occupancy.readers, occupancy.writers), (0, 0), "writer not alone");
+                occupancy.writers += 1;
+            }
+            yield_point().await;
+            *value += 1;
+            writer_occupancy.borrow_mut().writers -= 1;
+        }));
+
+        let (upgrade_lock, upgrade_occupancy) = (Rc::clone(&lock), Rc::clone(&occupancy));
+        tasks.push(Box::pin(async move {
+            let value = upgrade_lock.upgradable_read().await;
+            assert_eq!(upgrade_occupancy.borrow().writers, 0, "upgradable reader alongside a writer");
+            yield_point().await;
+            let mut value = value.upgrade().await;
+            {
+                let mut occupancy = upgrade_occupancy.borrow_mut();
+                assert_eq!((occupancy.readers, occupancy.writers), (0, 0), "upgraded writer not alone");
+                occupancy.writers += 1;
+            }
+            yield_point().await;
+            *value += 1;
+            upgrade_occupancy.borrow_mut().writers -= 1;
+            let value = value.downgrade();
+            assert_eq!(upgrade_occupancy.borrow().writers, 0, "downgraded reader alongside a writer");
+            drop(value);
+        }));
+
+        Scenario::new(tasks).with_check(move || {
+            let occupancy = occupancy.borrow();
+            assert_eq!((occupancy.readers, occupancy.writers), (0, 0));
+            assert_eq!(*lock.try_read().expect("lock left held"), 2, "lost write");
+        })
+    }
+
+    #[test]
+    fn read_write_lock_keeps_mutual_exclusion_under_every_interleaving() {
+        for policy in LockPolicy::ALL {
+            match explore(|| read_write_scenario(policy), 50_000) {
+                Ok(exploration) => {
+                    assert!(exploration.exhaustive, "{}: {:?}", policy, exploration);
+                    assert!(exploration.schedules > 1, "{}: nothing to interleave", policy);
+                }
+                Err(failure) => panic!("{}: {}", policy, failure),
+            }
+        }
+    }
+
+    /// Customers arriving at a small shop, some of them impatient, served by `barbers`.
+    fn barber_scenario(barbers: u32) -> Scenario {
+        let customers = 3;
+        let shop = Rc::new(BarberShop::new(barbers, 1, customers));
+        let outcomes = Rc::new(RefCell::new(Vec::new()));
+        let mut tasks: Vec<Task> = vec![];
+
+        for barber_id in 1..=barbers {
+            let shop = Rc::clone(&shop);
+            tasks.push(Box::pin(async move { shop.cut_hair(barber_id).await }));
+        }
+
+        for id in 1..=customers {
+            let (shop, outcomes) = (Rc::clone(&shop), Rc::clone(&outcomes));
+            let class = if id == 3 { CustomerClass::Vip } else { CustomerClass::Regular };
+            let patience = (id == 2).then(|| Duration::from_secs(1));
+            tasks.push(Box::pin(async move {
+                let outcome = shop.arrive_customer(id, class, Duration::from_secs(2), patience).await;
+                outcomes.borrow_mut().push((id, outcome));
+            }));
+        }
+
+        Scenario::new(tasks).with_check(move || {
+            let mut outcomes = outcomes.borrow().clone();
+            outcomes.sort_by_key(|&(id, _)| id);
+            let ids: Vec<u32> = outcomes.iter().map(|&(id, _)| id).collect();
+            assert_eq!(ids, (1..=customers).collect::<Vec<_>>(), "customer lost");
+
+            let count = |wanted| outcomes.iter().filter(|&&(_, outcome)| outcome == wanted).count() as u32;
+            let state = shop.state.try_lock().unwrap();
+            let stats = [&state.regular_stats, &state.vip_stats];
+            assert_eq!(stats.iter().map(|s| s.served).sum::<u32>(), count(CustomerOutcome::Served), "customer served twice");
+            assert_eq!(stats.iter().map(|s| s.balked).sum::<u32>(), count(CustomerOutcome::Balked));
+            assert_eq!(stats.iter().map(|s| s.reneged).sum::<u32>(), count(CustomerOutcome::Reneged));
+            assert_eq!(state.remaining_customers, 0);
+            assert_eq!(state.waiting_len(), 0, "customer left in the waiting room");
+        })
+    }
+
+    #[test]
+    fn barber_shop_serves_every_customer_once_under_every_interleaving() {
+        for barbers in 1..=2 {
+            match explore(|| barber_scenario(barbers), 50_000) {
+                Ok(exploration) => {
+                    assert!(exploration.exhaustive, "{} barbers: {:?}", barbers, exploration);
+                    assert!(exploration.schedules > 1, "{} barbers: nothing to interleave", barbers);
+                }
+                Err(failure) => panic!("{} barbers: {}", barbers, failure),
+            }
+        }
+    }
+}
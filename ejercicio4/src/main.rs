@@ -1,13 +1,39 @@
 mod barber;
 mod barber_impl;
+mod config;
+mod distribution;
+#[cfg(test)]
+mod interleaving;
+mod read_write_benchmark;
 mod read_write_lock;
 mod read_write_lock_impl;
 mod menu;
+mod queueing;
 
 use std::io;
+use std::process;
+use config::Settings;
 
 #[tokio::main]
 async fn main() {
+    let mut settings = match Settings::from_args(std::env::args().skip(1)) {
+        Ok(Some(settings)) => settings,
+        Ok(None) => {
+            print!("{}", config::usage());
+            return;
+        }
+        Err(error) => {
+            eprintln!("Error: {}.", error);
+            eprintln!("Use --help para ver las opciones disponibles.");
+            process::exit(2);
+        }
+    };
+
+    if let Some(problem) = settings.problem {
+        menu::run(problem, &settings).await;
+        return;
+    }
+
     let mut answer = String::new();
 
     loop {
@@ -15,6 +41,7 @@ async fn main() {
         println!("Elija una opción:");
         println!("1. Problema 12");
         println!("2. Problema 13");
+        println!("3. Problema 13: comparar políticas de lectores/escritores");
         println!("0. Salir");
 
         answer.clear();
@@ -23,8 +50,9 @@ async fn main() {
             .expect("Fallo al leer la entrada");
 
         match answer.trim() {
-            "1" => menu::problem_12().await,
-            "2" => menu::problem_13().await,
+            "1" => menu::problem_12(&mut settings).await,
+            "2" => menu::problem_13(&mut settings).await,
+            "3" => menu::problem_13_policies(&settings).await,
             "0" => {
                 println!("Hasta luego.");
                 break;
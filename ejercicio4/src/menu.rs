@@ -1,10 +1,76 @@
+use std::io::{self, Write};
 use crate::barber_impl::run_barber_shop;
+use crate::config::{self, Problem, Settings, READERS_WRITERS_KEYS, SHOP_KEYS};
+use crate::read_write_benchmark::run_policy_benchmark;
 use crate::read_write_lock_impl::run_readers_writers;
 
-pub async fn problem_12() {
-    run_barber_shop().await;
+pub async fn problem_12(settings: &mut Settings) {
+    for key in SHOP_KEYS {
+        prompt_setting(settings, key);
+    }
+    run(Problem::BarberShop, settings).await;
 }
 
-pub async fn problem_13() {
-    run_readers_writers().await;
+pub async fn problem_13(settings: &mut Settings) {
+    for key in READERS_WRITERS_KEYS {
+        prompt_setting(settings, key);
+    }
+    run(Problem::ReadersWriters, settings).await;
+}
+
+pub async fn problem_13_policies(settings: &Settings) {
+    run(Problem::Policies, settings).await;
+}
+
+/// Validates the parameters of a scenario and runs it, or explains why it cannot run.
+///
+/// # Arguments
+///
+/// * `problem` - The scenario to run.
+/// * `settings` - The parameters of every scenario.
+pub async fn run(problem: Problem, settings: &Settings) {
+    let valid = match problem {
+        Problem::BarberShop => settings.shop.validate(),
+        Problem::ReadersWriters => settings.readers_writers.validate(),
+        Problem::Policies => Ok(()),
+    };
+    if let Err(error) = valid {
+        println!("Configuración inválida: {}.", error);
+        return;
+    }
+
+    match problem {
+        Problem::BarberShop => run_barber_shop(settings.shop.clone()).await,
+        Problem::ReadersWriters => run_readers_writers(settings.readers_writers.clone()).await,
+        Problem::Policies => run_policy_benchmark().await,
+    }
+}
+
+/// Asks the user for a parameter, keeping its current value when the answer is empty
+/// and asking again when it cannot be parsed.
+///
+/// # Arguments
+///
+/// * `settings` - The parameters being edited.
+/// * `key` - The name of the parameter.
+fn prompt_setting(settings: &mut Settings, key: &str) {
+    loop {
+        print!("{} [{}]: ", config::description(key), settings.get(key));
+        io::stdout().flush().unwrap();
+
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).unwrap_or(0) == 0 {
+            return;
+        }
+
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return;
+        }
+
+        match settings.set(key, answer) {
+            Ok(()) => return,
+            Err(error) => println!("{}. Intente de nuevo.", error),
+        }
+    }
 }
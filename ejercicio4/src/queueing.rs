@@ -0,0 +1,127 @@
+/// Steady-state figures of a queueing system.
+#[derive(Clone, Copy, Debug)]
+pub struct QueueMetrics {
+    pub mean_queue_length: f64, // Average number of customers waiting (Lq)
+    pub mean_wait: f64,         // Average wait in the queue of admitted customers, in seconds (Wq)
+    pub utilization: f64,       // Fraction of time each server is busy
+    pub blocking: f64,          // Probability that an arriving customer finds the system full
+}
+
+/// Computes the closed-form M/M/c/K predictions.
+///
+/// # Arguments
+///
+/// * `lambda` - Arrival rate, in customers per second.
+/// * `mu` - Service rate of one server, in customers per second.
+/// * `c` - Number of servers.
+/// * `k` - System capacity (servers plus waiting places), at least `c`.
+pub fn mmck(lambda: f64, mu: f64, c: u32, k: u32) -> QueueMetrics {
+    let offered = lambda / mu;
+    let c_f = c as f64;
+
+    // Unnormalized probabilities of having n customers in the system.
+    let mut weights = Vec::with_capacity(k as usize + 1);
+    let mut term = 1.0;
+    for n in 0..=k {
+        if n > 0 {
+            term *= if n <= c { offered / n as f64 } else { offered / c_f };
+        }
+        weights.push(term);
+    }
+    let total: f64 = weights.iter().sum();
+    let probability = |n: u32| weights[n as usize] / total;
+
+    let mean_queue_length: f64 = (c..=k).map(|n| (n - c) as f64 * probability(n)).sum();
+    let blocking = probability(k);
+    let effective_lambda = lambda * (1.0 - blocking);
+
+    QueueMetrics {
+        mean_queue_length,
+        mean_wait: if effective_lambda > 0.0 { mean_queue_length / effective_lambda } else { 0.0 },
+        utilization: effective_lambda / (c_f * mu),
+        blocking,
+    }
+}
+
+/// Computes the closed-form M/M/c predictions (unlimited waiting room).
+///
+/// # Arguments
+///
+/// * `lambda` - Arrival rate, in customers per second.
+/// * `mu` - Service rate of one server, in customers per second.
+/// * `c` - Number of servers.
+///
+/// # Returns
+///
+/// `None` if the system is unstable (`lambda >= c * mu`).
+pub fn mmc(lambda: f64, mu: f64, c: u32) -> Option<QueueMetrics> {
+    let c_f = c as f64;
+    let rho = lambda / (c_f * mu);
+    if c == 0 || rho >= 1.0 {
+        return None;
+    }
+
+    let offered = lambda / mu;
+    let mut term = 1.0;
+    let mut sum = 1.0;
+    for n in 1..c {
+        term *= offered / n as f64;
+        sum += term;
+    }
+    let last = term * offered / c_f / (1.0 - rho);
+
+    // Erlang C: probability that an arriving customer has to wait.
+    let wait_probability = last / (sum + last);
+    let mean_wait = wait_probability / (c_f * mu - lambda);
+
+    Some(QueueMetrics {
+        mean_queue_length: lambda * mean_wait,
+        mean_wait,
+        utilization: rho,
+        blocking: 0.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn mm1_matches_the_textbook_values() {
+        // λ = 1, μ = 2: ρ = 0.5, Lq = ρ² / (1 - ρ) = 0.5 and Wq = Lq / λ = 0.5.
+        let metrics = mmc(1.0, 2.0, 1).unwrap();
+        assert_close(metrics.mean_queue_length, 0.5);
+        assert_close(metrics.mean_wait, 0.5);
+        assert_close(metrics.utilization, 0.5);
+        assert_close(metrics.blocking, 0.0);
+    }
+
+    #[test]
+    fn mmc_is_unstable_at_full_load() {
+        assert!(mmc(2.0, 1.0, 2).is_none());
+        assert!(mmc(3.0, 1.0, 2).is_none());
+        assert!(mmc(1.0, 1.0, 0).is_none());
+    }
+
+    #[test]
+    fn mmck_without_waiting_room_has_no_queue() {
+        let metrics = mmck(3.0, 1.0, 2, 2);
+        assert_close(metrics.mean_queue_length, 0.0);
+        assert_close(metrics.mean_wait, 0.0);
+        // Erlang B with offered load 3 and 2 servers: (9/2) / (1 + 3 + 9/2).
+        assert_close(metrics.blocking, 4.5 / 8.5);
+    }
+
+    #[test]
+    fn mmck_approaches_mmc_with_a_large_room() {
+        let unlimited = mmc(1.5, 1.0, 2).unwrap();
+        let limited = mmck(1.5, 1.0, 2, 200);
+        assert_close(limited.mean_queue_length, unlimited.mean_queue_length);
+        assert_close(limited.mean_wait, unlimited.mean_wait);
+        assert_close(limited.utilization, unlimited.utilization);
+    }
+}
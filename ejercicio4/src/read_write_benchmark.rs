@@ -0,0 +1,148 @@
+/*
+Benchmark of the `ReadWriteLock` policies. Every policy is run under a read-heavy,
+a write-heavy and a mixed load. Readers and writers repeatedly take the lock, hold it
+for a short time and pause before the next operation. For each run the benchmark
+reports the throughput (operations per second) and how long writers had to wait,
+which shows how much each policy lets readers starve writers.
+*/
+
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+use crate::read_write_lock::{LockPolicy, ReadWriteLock};
+
+/// A load applied to the lock: how many readers and writers, and how much work each does.
+#[derive(Clone, Copy, Debug)]
+pub struct Workload {
+    pub name: &'static str,   // Name shown in the report
+    pub readers: u32,         // Number of reader tasks
+    pub writers: u32,         // Number of writer tasks
+    pub operations: u32,      // Operations performed by each task
+    pub hold: Duration,       // How long each operation holds the lock
+    pub pause: Duration,      // Pause between two operations of the same task
+}
+
+/// The loads every policy is measured under.
+pub const WORKLOADS: [Workload; 3] = [
+    Workload {
+        name: "read-heavy",
+        readers: 12,
+        writers: 1,
+        operations: 40,
+        hold: Duration::from_millis(4),
+        pause: Duration::ZERO,
+    },
+    Workload {
+        name: "write-heavy",
+        readers: 2,
+        writers: 6,
+        operations: 20,
+        hold: Duration::from_millis(2),
+        pause: Duration::from_millis(1),
+    },
+    Workload {
+        name: "mixed",
+        readers: 6,
+        writers: 3,
+        operations: 30,
+        hold: Duration::from_millis(2),
+        pause: Duration::from_millis(1),
+    },
+];
+
+/// Figures measured in one run of the benchmark.
+#[derive(Clone, Copy, Debug)]
+pub struct BenchmarkResult {
+    pub throughput: f64,          // Completed operations per second
+    pub max_writer_wait: Duration, // Longest time a writer waited for the lock
+    pub mean_writer_wait: Duration, // Average time a writer waited for the lock
+}
+
+/// Runs one workload against a lock with the given policy.
+///
+/// # Arguments
+///
+/// * `policy` - The policy of the lock.
+/// * `workload` - The load to apply.
+pub async fn run_workload(policy: LockPolicy, workload: Workload) -> BenchmarkResult {
+    let lock = Arc::new(ReadWriteLock::with_policy(0u64, policy));
+    let start = Instant::now();
+    let mut readers = vec![];
+    let mut writers = vec![];
+
+    for reader in 0..workload.readers {
+        let lock = Arc::clone(&lock);
+        readers.push(tokio::spawn(async move {
+            // Stagger the readers so their critical sections overlap instead of ending together.
+            tokio::time::sleep(workload.hold * reader / workload.readers.max(1)).await;
+            for _ in 0..workload.operations {
+                let value = lock.read().await;
+                tokio::time::sleep(workload.hold).await;
+                drop(value);
+                tokio::time::sleep(workload.pause).await;
+            }
+        }));
+    }
+
+    for _ in 0..workload.writers {
+        let lock = Arc::clone(&lock);
+        writers.push(tokio::spawn(async move {
+            let mut waits = Vec::with_capacity(workload.operations as usize);
+            for _ in 0..workload.operations {
+                let requested = Instant::now();
+                let mut value = lock.write().await;
+                waits.push(requested.elapsed());
+                *value += 1;
+                tokio::time::sleep(workload.hold).await;
+                drop(value);
+                tokio::time::sleep(workload.pause).await;
+            }
+            waits
+        }));
+    }
+
+    for reader in readers {
+        reader.await.unwrap();
+    }
+    let mut waits = vec![];
+    for writer in writers {
+        waits.extend(writer.await.unwrap());
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let operations = (workload.readers + workload.writers) * workload.operations;
+
+    BenchmarkResult {
+        throughput: operations as f64 / elapsed,
+        max_writer_wait: waits.iter().copied().max().unwrap_or_default(),
+        mean_writer_wait: if waits.is_empty() {
+            Duration::ZERO
+        } else {
+            waits.iter().sum::<Duration>() / waits.len() as u32
+        },
+    }
+}
+
+/// Runs every policy under every workload and prints a table per workload.
+pub async fn run_policy_benchmark() {
+    for workload in WORKLOADS {
+        println!(
+            "===== {} load: {} readers, {} writers, {} operations each =====",
+            workload.name, workload.readers, workload.writers, workload.operations
+        );
+        println!(
+            "{:<18} {:>12} {:>16} {:>17}",
+            "Policy", "Ops/s", "Max writer wait", "Mean writer wait"
+        );
+
+        for policy in LockPolicy::ALL {
+            let result = run_workload(policy, workload).await;
+            println!(
+                "{:<18} {:>12.1} {:>14.1}ms {:>15.1}ms",
+                policy.to_string(),
+                result.throughput,
+                result.max_writer_wait.as_secs_f64() * 1000.0,
+                result.mean_writer_wait.as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
@@ -1,20 +1,253 @@
-use std::sync::Arc;
-use tokio::sync::{Mutex, Semaphore};
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tokio::sync::Notify;
+use tokio::time::Duration;
+use crate::distribution::TimeDistribution;
 
-/// Represents a reader-writer lock allowing multiple readers or a single writer.
-pub struct ReadWriteLock {
-    pub readers: Arc<Mutex<u32>>,          // Count of current readers
-    pub writer: Arc<Semaphore>,           // Semaphore for writers
-    pub read_lock: Arc<Semaphore>,        // Semaphore for reader access control
+/// Decides which waiting task enters the lock next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LockPolicy {
+    /// Readers enter whenever no writer holds the lock. Writers may starve.
+    ReaderPreferring,
+    /// New readers wait while a writer is waiting. Readers may starve.
+    WriterPreferring,
+    /// Tasks enter in arrival order; consecutive readers share the lock.
+    Fifo,
+    /// Readers and writers alternate: readers wait for at most one writer, and a writer
+    /// waits only for the readers that were already waiting when the previous writer left.
+    PhaseFair,
 }
 
-impl ReadWriteLock {
-    /// Creates a new `ReadWriteLock`.
-    pub fn new() -> Self {
+impl LockPolicy {
+    /// Every available policy, in the order they are reported.
+    pub const ALL: [LockPolicy; 4] = [
+        LockPolicy::ReaderPreferring,
+        LockPolicy::WriterPreferring,
+        LockPolicy::Fifo,
+        LockPolicy::PhaseFair,
+    ];
+}
+
+impl fmt::Display for LockPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LockPolicy::ReaderPreferring => "reader-preferring",
+            LockPolicy::WriterPreferring => "writer-preferring",
+            LockPolicy::Fifo => "fifo",
+            LockPolicy::PhaseFair => "phase-fair",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for LockPolicy {
+    type Err = String;
+
+    /// Parses a policy by the name it is displayed with.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to parse.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        LockPolicy::ALL
+            .into_iter()
+            .find(|policy| policy.to_string() == text.trim())
+            .ok_or_else(|| {
+                let names: Vec<String> = LockPolicy::ALL.iter().map(|policy| policy.to_string()).collect();
+                format!("política '{}' no reconocida (use {})", text.trim(), names.join(", "))
+            })
+    }
+}
+
+/// Parameters of a readers/writers demo run.
+#[derive(Clone, Debug)]
+pub struct ReadersWritersConfig {
+    pub readers: u32,                 // Number of reader tasks
+    pub writers: u32,                 // Number of writer tasks
+    pub iterations: u32,              // Times each reader and writer takes the lock
+    pub read_time: TimeDistribution,  // How long a reader holds the lock
+    pub write_time: TimeDistribution, // How long a writer holds the lock
+    pub start_gap: TimeDistribution,  // Time between two consecutive tasks starting
+    pub policy: LockPolicy,           // Policy of the lock
+    pub seed: u64,                    // Seed for every random draw of the run
+    pub time_scale: f64,              // Factor applied to every duration, below 1 to compress time
+}
+
+impl Default for ReadersWritersConfig {
+    fn default() -> Self {
+        ReadersWritersConfig {
+            readers: 3,
+            writers: 2,
+            iterations: 1,
+            read_time: TimeDistribution::Deterministic(Duration::from_secs(2)),
+            write_time: TimeDistribution::Deterministic(Duration::from_secs(2)),
+            start_gap: TimeDistribution::Deterministic(Duration::ZERO),
+            policy: LockPolicy::ReaderPreferring,
+            seed: 42,
+            time_scale: 1.0,
+        }
+    }
+}
+
+impl ReadersWritersConfig {
+    /// Checks that the parameters describe a run that can be executed.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.readers == 0 && self.writers == 0 {
+            return Err("se necesita al menos un lector o un escritor".to_string());
+        }
+        if self.iterations == 0 {
+            return Err("cada tarea debe tomar el cerrojo al menos una vez".to_string());
+        }
+        if !(self.time_scale.is_finite() && self.time_scale > 0.0) {
+            return Err(format!(
+                "la escala de tiempo debe ser un número positivo (se recibió {})",
+                self.time_scale
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Kind of access a task asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessKind {
+    Read,
+    UpgradableRead,
+    Write,
+}
+
+impl AccessKind {
+    /// Returns `true` for the kinds that share the lock with other readers.
+    pub fn is_read(self) -> bool {
+        matches!(self, AccessKind::Read | AccessKind::UpgradableRead)
+    }
+}
+
+/// A task waiting to enter the lock.
+#[derive(Clone, Copy, Debug)]
+pub struct Waiter {
+    pub ticket: u64,       // Arrival order of the task
+    pub kind: AccessKind,  // What the task asks for
+    pub phase: u64,        // Completed writes a phase-fair reader waits for
+}
+
+/// Bookkeeping of who currently holds the lock and who is waiting for it.
+#[derive(Default)]
+pub struct LockState {
+    pub readers: u32,               // Count of current readers
+    pub writer: bool,               // Whether a writer holds the lock
+    pub upgradable: bool,           // Whether an upgradable reader holds the lock
+    pub upgrading: bool,            // Whether the upgradable reader is waiting to become the writer
+    pub completed_writes: u64,      // Number of writers that have released the lock
+    pub next_ticket: u64,           // Ticket given to the next arriving task
+    pub queue: VecDeque<Waiter>,    // Waiting tasks, in arrival order
+}
+
+/// Represents a reader-writer lock protecting a value of type `T`, allowing
+/// multiple readers or a single writer.
+pub struct ReadWriteLock<T> {
+    pub(crate) policy: LockPolicy,       // Which waiting task enters next
+    pub(crate) state: Mutex<LockState>,  // Current readers and writer
+    pub(crate) changed: Notify,          // Wakes waiting tasks when the lock is released
+    pub(crate) data: UnsafeCell<T>,      // The protected value
+}
+
+// SAFETY: the value is only reached through the guards, and the lock hands out either
+// one `WriteGuard` or any number of `ReadGuard`s at a time.
+unsafe impl<T: Send> Send for ReadWriteLock<T> {}
+unsafe impl<T: Send + Sync> Sync for ReadWriteLock<T> {}
+
+impl<T> ReadWriteLock<T> {
+    /// Creates a new `ReadWriteLock` protecting `value` with the given policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to protect.
+    /// * `policy` - Which waiting task enters the lock next.
+    pub fn with_policy(value: T, policy: LockPolicy) -> Self {
         ReadWriteLock {
-            readers: Arc::new(Mutex::new(0)),
-            writer: Arc::new(Semaphore::new(1)),  // Only one writer allowed
-            read_lock: Arc::new(Semaphore::new(1)),  // Control access for readers
+            policy,
+            state: Mutex::new(LockState::default()),
+            changed: Notify::new(),
+            data: UnsafeCell::new(value),
         }
     }
-}
\ No newline at end of file
+
+    /// Consumes the lock and returns the protected value.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+/// Shared access to the value of a `ReadWriteLock`. Releases the read side on drop.
+pub struct ReadGuard<'a, T> {
+    pub(crate) lock: &'a ReadWriteLock<T>,
+}
+
+impl<T> Deref for ReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: while a read guard exists no writer can hold the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release_read();
+    }
+}
+
+/// Shared access to the value of a `ReadWriteLock` that can later be upgraded to
+/// exclusive access without releasing the lock. Only one upgradable reader exists at a
+/// time, alongside any number of plain readers. Releases its side of the lock on drop.
+pub struct UpgradableReadGuard<'a, T> {
+    pub(crate) lock: &'a ReadWriteLock<T>,
+}
+
+impl<T> Deref for UpgradableReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: while an upgradable guard exists no writer can hold the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for UpgradableReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release_upgradable();
+    }
+}
+
+/// Exclusive access to the value of a `ReadWriteLock`. Releases the write side on drop.
+pub struct WriteGuard<'a, T> {
+    pub(crate) lock: &'a ReadWriteLock<T>,
+}
+
+impl<T> Deref for WriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: a write guard is the only guard in existence.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for WriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: a write guard is the only guard in existence.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for WriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.release_write();
+    }
+}
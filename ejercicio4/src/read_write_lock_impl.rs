@@ -1,136 +1,736 @@
 /*
-The `ReadWriteLock` struct contains three fields: `readers`, `writer`, and `read_lock`.
-The `readers` field is a `Mutex<u32>` that stores the count of current readers.
-The `writer` field is a `Semaphore` with a permit count of 1, which controls writer access.
-The `read_lock` field is another `Semaphore` with a permit count of 1, which controls reader access.
+The `ReadWriteLock<T>` struct protects a value of type `T` and keeps its bookkeeping
+in a `LockState` guarded by a mutex: `readers` counts the current readers, `writer`
+tells whether a writer holds the lock and `queue` lists the waiting tasks in arrival
+order. Tasks that cannot enter wait on the `changed` notification, which is raised
+every time the lock is released or the queue changes.
 
-The `new` method creates a new `ReadWriteLock` instance.
+The `LockPolicy` chosen at construction decides who enters next: readers first
+(the default, as in the original exercise), writers first, strict arrival order, or
+phase-fair alternation between a group of readers and a single writer.
 
-The `read` method simulates a reader trying to read. It first acquires the `read_lock` semaphore
-to enter the reading section. It then increments the count of current readers and blocks writers
-if it's the first reader. After releasing the `read_lock` semaphore, it simulates reading for 2 seconds.
-Finally, it decrements the count of current readers and releases the writer lock if it's the last reader.
+The `read` method returns a `ReadGuard`, which derefs to `&T`. Several readers can hold
+the lock at the same time. The `write` method returns a `WriteGuard`, which derefs to
+`&mut T`, once nobody else holds the lock.
 
-The `write` method simulates a writer trying to write. It acquires the `writer` semaphore to
-enter the writing section, simulates writing for 2 seconds, and then releases the writer lock.
+The `upgradable_read` method returns an `UpgradableReadGuard`. At most one upgradable
+reader holds the lock at a time, next to plain readers, and writers stay out while it
+does. Its `upgrade` method waits for the plain readers to leave (new readers are held
+back meanwhile) and turns it into a `WriteGuard` without releasing the lock, so nothing
+can change between checking and modifying the value. A `WriteGuard` can be downgraded
+to a `ReadGuard` atomically, letting waiting readers in but no writer.
 
-The `main` function creates a new `ReadWriteLock` instance and spawns tasks for readers and writers.
-The readers and writers are created in separate loops, and each task is spawned using `tokio::spawn`.
-The `main` function waits for all tasks to finish using `task.await.unwrap()`.
+Each guard releases its side of the lock when it is dropped. `try_read` and `try_write`
+return `None` instead of waiting, and `into_inner` gives the value back.
+
+The `run_readers_writers` function creates a `ReadWriteLock<SharedRecord>` and spawns tasks
+for readers and writers. Writers update the record in two steps with a pause in between,
+and readers check that they never see a half-written record. A maintainer checks the
+record with an upgradable read, upgrades to top it up only if it is still low, and
+downgrades to keep reading it. The number of readers and writers, how many times each
+one takes the lock, how long it holds it and how far apart the tasks start come from a
+`ReadersWritersConfig`; every duration is multiplied by its time scale, so runs with
+hundreds of tasks finish quickly.
 
 To run the program, execute the following command:
 cargo run
+or, to skip the menu and pass the parameters directly:
+cargo run -- --problem 13 --readers 200 --writers 50 --iterations 3 --time-scale 0.01
 */
 
 /* Example Output:
-Reader 1 is the first reader, blocking writers.
-Reader 1 is reading.
+Reader 1 is reading. Record: version 0, author 0, value 0.
+Reader 2 is reading. Record: version 0, author 0, value 0.
+Reader 3 is reading. Record: version 0, author 0, value 0.
 Reader 1 has finished reading.
-Writer 1 is writing.
-Writer 1 has finished writing.
-Reader 3 is reading.
-Reader 3 has finished reading.
-Writer 2 is writing.
-Writer 2 has finished writing.
-Reader 2 is reading.
-Reader 2 has finished reading. */
-
-/*Reader 2 is the first reader, blocking writers.
-Reader 2 is reading.
 Reader 2 has finished reading.
-Writer 1 is writing.
-Writer 1 has finished writing.
-Reader 1 is reading.
-Reader 1 has finished reading.
-Reader 3 is reading.
 Reader 3 has finished reading.
+Writer 1 is writing.
+Writer 1 has finished writing. Record: version 1, author 1, value 10.
 Writer 2 is writing.
-Writer 2 has finished writing.*/
+Writer 2 has finished writing. Record: version 2, author 2, value 30.
+Final record: version 2, author 2, value 30. */
 
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use tokio::time::Duration;
-use crate::read_write_lock::ReadWriteLock;
+use crate::distribution::TimeDistribution;
+use crate::read_write_lock::{
+    AccessKind, LockPolicy, LockState, ReadGuard, ReadWriteLock, ReadersWritersConfig,
+    UpgradableReadGuard, Waiter, WriteGuard,
+};
 
-impl ReadWriteLock {
-    /// Handles a reader trying to read.
+impl LockState {
+    /// Creates a waiter for a task that is arriving now, without queueing it.
     ///
     /// # Arguments
     ///
-    /// * `id` - The ID of the reader.
-    async fn read(&self, id: u32) {
-        // Wait to enter the reading section
-        let _ = self.read_lock.acquire().await.unwrap();
+    /// * `kind` - What the task asks for.
+    fn arriving(&mut self, kind: AccessKind) -> Waiter {
+        let writer_present = self.writer || self.upgrading || self.waiting_writer(u64::MAX);
+        let waiter = Waiter {
+            ticket: self.next_ticket,
+            kind,
+            phase: self.completed_writes + writer_present as u64,
+        };
+        self.next_ticket += 1;
+        waiter
+    }
 
-        // Increment the count of current readers
-        {
-            let mut count = self.readers.lock().await;
-            *count += 1;
-            if *count == 1 {
-                // First reader blocks writers
-                println!("Reader {} is the first reader, blocking writers.", id);
-                let _ = self.writer.acquire().await.unwrap();
+    /// Returns `true` if a writer that arrived before `ticket` is waiting.
+    ///
+    /// # Arguments
+    ///
+    /// * `ticket` - Only waiters with a smaller ticket count.
+    fn waiting_writer(&self, ticket: u64) -> bool {
+        self.queue
+            .iter()
+            .any(|waiter| waiter.kind == AccessKind::Write && waiter.ticket < ticket)
+    }
+
+    /// Returns `true` if a reader (plain or upgradable) is waiting.
+    fn waiting_reader(&self) -> bool {
+        self.queue.iter().any(|waiter| waiter.kind.is_read())
+    }
+
+    /// Returns `true` if a phase-fair reader may enter: the writer phase it waited for
+    /// is over, or no writer is left to wait for.
+    ///
+    /// # Arguments
+    ///
+    /// * `waiter` - The reader.
+    fn reader_phase_done(&self, waiter: &Waiter) -> bool {
+        self.completed_writes >= waiter.phase || !self.waiting_writer(u64::MAX)
+    }
+
+    /// Decides whether `waiter` may enter the lock now under `policy`.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The policy of the lock.
+    /// * `waiter` - The task asking to enter.
+    pub fn admits(&self, policy: LockPolicy, waiter: &Waiter) -> bool {
+        // A pending upgrade holds back everyone, so the upgradable reader is not starved.
+        if self.writer || self.upgrading {
+            return false;
+        }
+
+        match (waiter.kind, policy) {
+            (AccessKind::UpgradableRead, _) if self.upgradable => false,
+            (AccessKind::Read | AccessKind::UpgradableRead, LockPolicy::ReaderPreferring) => true,
+            (AccessKind::Read | AccessKind::UpgradableRead, LockPolicy::WriterPreferring) => {
+                !self.waiting_writer(u64::MAX)
+            }
+            (AccessKind::Read | AccessKind::UpgradableRead, LockPolicy::Fifo) => {
+                !self.waiting_writer(waiter.ticket)
+            }
+            (AccessKind::Read | AccessKind::UpgradableRead, LockPolicy::PhaseFair) => {
+                self.reader_phase_done(waiter)
+            }
+
+            (AccessKind::Write, _) if self.readers > 0 || self.upgradable => false,
+            (AccessKind::Write, LockPolicy::ReaderPreferring) => {
+                !self.waiting_reader() && !self.waiting_writer(waiter.ticket)
+            }
+            (AccessKind::Write, LockPolicy::WriterPreferring) => !self.waiting_writer(waiter.ticket),
+            (AccessKind::Write, LockPolicy::Fifo) => {
+                self.queue.iter().all(|other| other.ticket >= waiter.ticket)
+            }
+            (AccessKind::Write, LockPolicy::PhaseFair) => {
+                !self.waiting_writer(waiter.ticket)
+                    && !self
+                        .queue
+                        .iter()
+                        .any(|other| other.kind.is_read() && self.reader_phase_done(other))
             }
         }
+    }
 
-        // Release the read lock for others
-        self.read_lock.add_permits(1);
+    /// Records that a task of the given kind entered the lock.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - What the task entered for.
+    fn enter(&mut self, kind: AccessKind) {
+        match kind {
+            AccessKind::Read => self.readers += 1,
+            AccessKind::UpgradableRead => self.upgradable = true,
+            AccessKind::Write => self.writer = true,
+        }
+    }
+}
 
-        // Simulate reading
-        println!("Reader {} is reading.", id);
-        println!("Reader {} has finished reading.", id);
-        tokio::time::sleep(Duration::from_secs(2)).await;
+/// Keeps a waiter in the queue of a lock and removes it if the waiting future is
+/// dropped before it enters.
+struct QueuedWaiter<'a, T> {
+    lock: &'a ReadWriteLock<T>,
+    ticket: u64,
+    admitted: bool,
+}
 
-        // Decrement the count of current readers
-        {
-            let mut count = self.readers.lock().await;
-            *count -= 1;
-            if *count == 0 {
-                self.writer.add_permits(1);
-            }
+impl<T> Drop for QueuedWaiter<'_, T> {
+    fn drop(&mut self) {
+        if !self.admitted {
+            let mut state = self.lock.state.lock().unwrap();
+            state.queue.retain(|waiter| waiter.ticket != self.ticket);
+            self.lock.changed.notify_waiters();
+        }
+    }
+}
+
+impl<T> ReadWriteLock<T> {
+    /// Acquires the lock for reading, waiting as long as the policy requires.
+    ///
+    /// # Returns
+    ///
+    /// A `ReadGuard` that releases the read side of the lock when dropped.
+    pub async fn read(&self) -> ReadGuard<'_, T> {
+        self.acquire(AccessKind::Read).await;
+        ReadGuard { lock: self }
+    }
+
+    /// Acquires the lock for writing, waiting as long as the policy requires.
+    ///
+    /// # Returns
+    ///
+    /// A `WriteGuard` that releases the lock when dropped.
+    pub async fn write(&self) -> WriteGuard<'_, T> {
+        self.acquire(AccessKind::Write).await;
+        WriteGuard { lock: self }
+    }
+
+    /// Acquires the lock for an upgradable read, waiting as long as the policy requires
+    /// and while another upgradable reader holds the lock.
+    ///
+    /// # Returns
+    ///
+    /// An `UpgradableReadGuard` that can later be upgraded to a `WriteGuard`.
+    pub async fn upgradable_read(&self) -> UpgradableReadGuard<'_, T> {
+        self.acquire(AccessKind::UpgradableRead).await;
+        UpgradableReadGuard { lock: self }
+    }
+
+    /// Tries to acquire the lock for reading without waiting.
+    ///
+    /// # Returns
+    ///
+    /// A `ReadGuard`, or `None` if the policy would make a new reader wait.
+    pub fn try_read(&self) -> Option<ReadGuard<'_, T>> {
+        self.try_acquire(AccessKind::Read).then(|| ReadGuard { lock: self })
+    }
+
+    /// Tries to acquire the lock for writing without waiting.
+    ///
+    /// # Returns
+    ///
+    /// A `WriteGuard`, or `None` if the policy would make a new writer wait.
+    pub fn try_write(&self) -> Option<WriteGuard<'_, T>> {
+        self.try_acquire(AccessKind::Write).then(|| WriteGuard { lock: self })
+    }
+
+    /// Enters the lock if a task arriving now would be admitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - What the task asks for.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the task entered the lock.
+    fn try_acquire(&self, kind: AccessKind) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let waiter = state.arriving(kind);
+        if state.admits(self.policy, &waiter) {
+            state.enter(kind);
+            true
+        } else {
+            false
         }
     }
 
-    /// Handles a writer trying to write.
+    /// Enters the lock, queueing the task until the policy admits it.
     ///
     /// # Arguments
     ///
-    /// * `id` - The ID of the writer.
-    async fn write(&self, id: u32) {
-        // Wait to enter the writing section
-        let _ = self.writer.acquire().await.unwrap();
+    /// * `kind` - What the task asks for.
+    async fn acquire(&self, kind: AccessKind) {
+        let ticket = {
+            let mut state = self.state.lock().unwrap();
+            let waiter = state.arriving(kind);
+            if state.admits(self.policy, &waiter) {
+                state.enter(kind);
+                return;
+            }
+            state.queue.push_back(waiter);
+            waiter.ticket
+        };
+
+        let mut queued = QueuedWaiter { lock: self, ticket, admitted: false };
+
+        loop {
+            // Register for the notification before checking, so a change in between is not lost.
+            let notified = self.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut state = self.state.lock().unwrap();
+                let position = state
+                    .queue
+                    .iter()
+                    .position(|waiter| waiter.ticket == ticket)
+                    .unwrap();
+                if state.admits(self.policy, &state.queue[position]) {
+                    state.queue.remove(position);
+                    state.enter(kind);
+                    queued.admitted = true;
+                    // Leaving the queue may let the next waiters in (for example, more readers).
+                    self.changed.notify_waiters();
+                    return;
+                }
+            }
+
+            notified.await;
+        }
+    }
+
+    /// Releases the read side of the lock. Called when a `ReadGuard` is dropped.
+    pub(crate) fn release_read(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.readers -= 1;
+        if state.readers == 0 {
+            self.changed.notify_waiters();
+        }
+    }
+
+    /// Releases the upgradable read of the lock. Called when an `UpgradableReadGuard` is
+    /// dropped, including while its upgrade was still waiting.
+    pub(crate) fn release_upgradable(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.upgradable = false;
+        state.upgrading = false;
+        self.changed.notify_waiters();
+    }
+
+    /// Releases the write side of the lock. Called when a `WriteGuard` is dropped.
+    pub(crate) fn release_write(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.writer = false;
+        state.completed_writes += 1;
+        self.changed.notify_waiters();
+    }
+}
+
+impl<'a, T> UpgradableReadGuard<'a, T> {
+    /// Turns the upgradable read into a write without releasing the lock.
+    ///
+    /// New readers are held back while the plain readers already inside finish. No writer
+    /// can enter in between, so what was read through this guard is still current.
+    ///
+    /// # Returns
+    ///
+    /// A `WriteGuard` over the same lock.
+    pub async fn upgrade(self) -> WriteGuard<'a, T> {
+        let lock = self.lock;
+        lock.state.lock().unwrap().upgrading = true;
+
+        loop {
+            // Register for the notification before checking, so a release in between is not lost.
+            let notified = lock.changed.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            {
+                let mut state = lock.state.lock().unwrap();
+                if state.readers == 0 {
+                    state.upgradable = false;
+                    state.upgrading = false;
+                    state.writer = true;
+                    // The write guard now owns the lock, so this guard must not release it.
+                    std::mem::forget(self);
+                    return WriteGuard { lock };
+                }
+            }
+
+            notified.await;
+        }
+    }
+}
+
+impl<'a, T> WriteGuard<'a, T> {
+    /// Turns the write into a plain read without releasing the lock. Waiting readers may
+    /// enter right away, but no writer can change the value in between.
+    ///
+    /// # Returns
+    ///
+    /// A `ReadGuard` over the same lock.
+    pub fn downgrade(self) -> ReadGuard<'a, T> {
+        let lock = self.lock;
+        {
+            let mut state = lock.state.lock().unwrap();
+            state.writer = false;
+            state.completed_writes += 1;
+            state.readers += 1;
+            lock.changed.notify_waiters();
+        }
+        // The read guard now owns the lock, so this guard must not release it.
+        std::mem::forget(self);
+        ReadGuard { lock }
+    }
+}
+
+/// Record shared by the readers and writers of the demo.
+///
+/// Writers update it in two steps, so `checksum` only matches the other fields when
+/// the record has been written completely.
+#[derive(Clone, Debug, Default)]
+pub struct SharedRecord {
+    pub version: u64,   // Number of writes applied so far
+    pub author: u32,    // ID of the last writer
+    pub value: u64,     // Value written by the last writer
+    pub checksum: u64,  // Derived from the other fields once a write is complete
+}
 
-        // Simulate writing
-        println!("Writer {} is writing.", id);
-        println!("Writer {} has finished writing.", id);
-        tokio::time::sleep(Duration::from_secs(2)).await;
+impl SharedRecord {
+    /// Computes the checksum that matches the current fields.
+    pub fn expected_checksum(&self) -> u64 {
+        self.version * 1_000_003 + self.author as u64 * 1_009 + self.value
+    }
 
-        // Release the writer lock
-        self.writer.add_permits(1);
+    /// Returns `true` if the record was not caught halfway through a write.
+    pub fn is_consistent(&self) -> bool {
+        self.checksum == self.expected_checksum()
     }
 }
 
-pub async fn run_readers_writers() {
-    let rw_lock = Arc::new(ReadWriteLock::new());
+/// Writes a new value into the record in two steps, pausing in between while still
+/// holding the lock.
+///
+/// # Arguments
+///
+/// * `record` - The write guard over the record.
+/// * `id` - The ID of the writer.
+/// * `pause` - How long the writer pauses between the two steps.
+pub async fn write_record(record: &mut WriteGuard<'_, SharedRecord>, id: u32, pause: Duration) {
+    record.version += 1;
+    record.author = id;
+    record.value += id as u64 * 10;
+    tokio::time::sleep(pause).await;
+    record.checksum = record.expected_checksum();
+}
+
+/// Runs the readers/writers demo described by `config`.
+///
+/// # Arguments
+///
+/// * `config` - Number of readers and writers, how long they hold the lock and how often.
+pub async fn run_readers_writers(config: ReadersWritersConfig) {
+    if config.time_scale != 1.0 {
+        println!("Time is scaled by {}.", config.time_scale);
+    }
+    let rw_lock = Arc::new(ReadWriteLock::with_policy(SharedRecord::default(), config.policy));
+    let torn_reads = Arc::new(AtomicU32::new(0));
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut start = Duration::ZERO;
     let mut tasks = vec![];
 
+    // Every random draw happens here, in task order, so the seed fixes the whole run.
+    let draw = |distribution: TimeDistribution, rng: &mut StdRng| {
+        distribution.scaled(config.time_scale).sample(rng)
+    };
+
     // Create readers
-    for i in 1..=3 {
+    for i in 1..=config.readers {
+        start += draw(config.start_gap, &mut rng);
+        let holds: Vec<Duration> = (0..config.iterations).map(|_| draw(config.read_time, &mut rng)).collect();
         let rw_lock_clone = Arc::clone(&rw_lock);
+        let torn_reads = Arc::clone(&torn_reads);
         tasks.push(tokio::spawn(async move {
-            rw_lock_clone.read(i).await;
+            tokio::time::sleep(start).await;
+            for hold in holds {
+                let record = match rw_lock_clone.try_read() {
+                    Some(record) => record,
+                    None => {
+                        println!("Reader {} has to wait for the writer.", i);
+                        rw_lock_clone.read().await
+                    }
+                };
+                println!(
+                    "Reader {} is reading. Record: version {}, author {}, value {}.",
+                    i, record.version, record.author, record.value
+                );
+                if !record.is_consistent() {
+                    println!("Reader {} found a torn record!", i);
+                    torn_reads.fetch_add(1, Ordering::Relaxed);
+                }
+                tokio::time::sleep(hold).await;
+                println!("Reader {} has finished reading.", i);
+            }
         }));
     }
 
     // Create writers
-    for i in 1..=2 {
+    for i in 1..=config.writers {
+        start += draw(config.start_gap, &mut rng);
+        let holds: Vec<Duration> = (0..config.iterations).map(|_| draw(config.write_time, &mut rng)).collect();
         let rw_lock_clone = Arc::clone(&rw_lock);
         tasks.push(tokio::spawn(async move {
-            rw_lock_clone.write(i).await;
+            tokio::time::sleep(start).await;
+            for hold in holds {
+                let mut record = match rw_lock_clone.try_write() {
+                    Some(record) => record,
+                    None => {
+                        println!("Writer {} has to wait for the lock.", i);
+                        rw_lock_clone.write().await
+                    }
+                };
+                println!("Writer {} is writing.", i);
+                write_record(&mut record, i, hold).await;
+                println!(
+                    "Writer {} has finished writing. Record: version {}, author {}, value {}.",
+                    i, record.version, record.author, record.value
+                );
+            }
         }));
     }
 
+    // Create a maintainer that tops up the record only if no writer has done it yet
+    let maintainer_hold = draw(config.write_time, &mut rng);
+    let rw_lock_clone = Arc::clone(&rw_lock);
+    let id = config.writers + 1;
+    tasks.push(tokio::spawn(async move {
+        let record = rw_lock_clone.upgradable_read().await;
+        println!("Maintainer is checking the record. Value: {}.", record.value);
+
+        if record.value >= 30 {
+            println!("Maintainer found the record up to date.");
+            return;
+        }
+
+        // Upgrading keeps the lock, so the value checked above cannot change before the write.
+        let mut record = record.upgrade().await;
+        println!("Maintainer is topping up the record.");
+        write_record(&mut record, id, maintainer_hold).await;
+
+        // Downgrading lets waiting readers in while the maintainer still reads the record.
+        let record = record.downgrade();
+        println!(
+            "Maintainer has finished. Record: version {}, author {}, value {}.",
+            record.version, record.author, record.value
+        );
+    }));
+
     // Wait for all tasks to finish
     for task in tasks {
         task.await.unwrap();
     }
-}
\ No newline at end of file
+
+    let record = Arc::try_unwrap(rw_lock).ok().unwrap().into_inner();
+    println!(
+        "Final record: version {}, author {}, value {}.",
+        record.version, record.author, record.value
+    );
+    println!("Torn reads: {}.", torn_reads.load(Ordering::Relaxed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn readers_never_see_torn_records() {
+        let lock = Arc::new(ReadWriteLock::with_policy(SharedRecord::default(), LockPolicy::ReaderPreferring));
+        let mut tasks = vec![];
+
+        for id in 1..=4 {
+            let lock = Arc::clone(&lock);
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..25 {
+                    let mut record = lock.write().await;
+                    write_record(&mut record, id, Duration::from_micros(50)).await;
+                }
+            }));
+        }
+        for _ in 0..8 {
+            let lock = Arc::clone(&lock);
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..100 {
+                    let record = lock.read().await;
+                    assert!(record.is_consistent(), "torn read: {:?}", *record);
+                    drop(record);
+                    tokio::task::yield_now().await;
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let record = Arc::try_unwrap(lock).ok().unwrap().into_inner();
+        assert_eq!(record.version, 100);
+        assert!(record.is_consistent());
+    }
+
+    #[test]
+    fn try_lock_respects_the_other_side() {
+        let lock = ReadWriteLock::with_policy(5, LockPolicy::ReaderPreferring);
+
+        let first = lock.try_read().unwrap();
+        let second = lock.try_read().unwrap();
+        assert_eq!(*first + *second, 10);
+        assert!(lock.try_write().is_none());
+        drop((first, second));
+
+        let mut writer = lock.try_write().unwrap();
+        *writer = 7;
+        assert!(lock.try_read().is_none());
+        assert!(lock.try_write().is_none());
+        drop(writer);
+
+        assert_eq!(*lock.try_read().unwrap(), 7);
+        assert_eq!(lock.into_inner(), 7);
+    }
+
+    /// Starts a writer that queues behind the current reader, and returns once it is waiting.
+    async fn queue_writer(lock: &Arc<ReadWriteLock<u32>>) -> tokio::task::JoinHandle<()> {
+        let writer_lock = Arc::clone(lock);
+        let writer = tokio::spawn(async move {
+            *writer_lock.write().await += 1;
+        });
+        while lock.state.lock().unwrap().queue.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        writer
+    }
+
+    #[tokio::test]
+    async fn policies_decide_whether_new_readers_pass_a_waiting_writer() {
+        for (policy, reader_passes) in [
+            (LockPolicy::ReaderPreferring, true),
+            (LockPolicy::WriterPreferring, false),
+            (LockPolicy::Fifo, false),
+            (LockPolicy::PhaseFair, false),
+        ] {
+            let lock = Arc::new(ReadWriteLock::with_policy(0, policy));
+            let reader = lock.read().await;
+            let writer = queue_writer(&lock).await;
+
+            assert_eq!(lock.try_read().is_some(), reader_passes, "{}", policy);
+            drop(reader);
+            writer.await.unwrap();
+            assert_eq!(*lock.read().await, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn phase_fair_readers_waiting_for_a_writer_enter_before_the_next_writer() {
+        let lock = Arc::new(ReadWriteLock::with_policy(0, LockPolicy::PhaseFair));
+        let first_writer = lock.write().await;
+
+        // A second writer queues first, then a reader queues during the first writer's phase.
+        let second_writer = queue_writer(&lock).await;
+        let reader_lock = Arc::clone(&lock);
+        let reader = tokio::spawn(async move { *reader_lock.read().await });
+        while lock.state.lock().unwrap().queue.len() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        drop(first_writer);
+        // The reader sees the value before the second writer changes it.
+        assert_eq!(reader.await.unwrap(), 0);
+        second_writer.await.unwrap();
+        assert_eq!(*lock.read().await, 1);
+    }
+
+    #[tokio::test]
+    async fn cancelled_waiter_leaves_the_queue() {
+        let lock = Arc::new(ReadWriteLock::with_policy(0, LockPolicy::Fifo));
+        let reader = lock.read().await;
+        let writer = queue_writer(&lock).await;
+
+        writer.abort();
+        let _ = writer.await;
+        assert!(lock.state.lock().unwrap().queue.is_empty());
+        assert!(lock.try_read().is_some());
+        drop(reader);
+        assert!(lock.try_write().is_some());
+    }
+
+    #[tokio::test]
+    async fn upgradable_read_coexists_with_readers_only() {
+        let lock = ReadWriteLock::with_policy(0, LockPolicy::ReaderPreferring);
+        let upgradable = lock.upgradable_read().await;
+
+        assert!(lock.try_read().is_some());
+        assert!(lock.try_write().is_none());
+        assert!(lock.state.lock().unwrap().upgradable);
+        drop(upgradable);
+        assert!(lock.try_write().is_some());
+    }
+
+    #[tokio::test]
+    async fn upgrade_waits_for_readers_and_holds_new_ones_back() {
+        let lock = ReadWriteLock::with_policy(0, LockPolicy::ReaderPreferring);
+        let reader = lock.read().await;
+        let upgradable = lock.upgradable_read().await;
+
+        let upgrade = async {
+            let mut value = upgradable.upgrade().await;
+            *value += 1;
+            let value = value.downgrade();
+            assert!(lock.try_write().is_none());
+            *value
+        };
+        let release_reader = async {
+            while !lock.state.lock().unwrap().upgrading {
+                tokio::task::yield_now().await;
+            }
+            assert!(lock.try_read().is_none());
+            drop(reader);
+        };
+
+        let (value, ()) = tokio::join!(upgrade, release_reader);
+        assert_eq!(value, 1);
+        assert!(lock.try_write().is_some());
+    }
+
+    #[tokio::test]
+    async fn downgrade_lets_readers_in_but_not_writers() {
+        let lock = ReadWriteLock::with_policy(0, LockPolicy::ReaderPreferring);
+        let mut writer = lock.write().await;
+        *writer = 5;
+
+        let reader = writer.downgrade();
+        assert_eq!(*lock.try_read().unwrap(), 5);
+        assert!(lock.try_write().is_none());
+        drop(reader);
+        assert!(lock.try_write().is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn check_then_modify_never_overshoots() {
+        let lock = Arc::new(ReadWriteLock::with_policy(0u32, LockPolicy::ReaderPreferring));
+        let mut tasks = vec![];
+
+        for _ in 0..16 {
+            let lock = Arc::clone(&lock);
+            tasks.push(tokio::spawn(async move {
+                for _ in 0..20 {
+                    let value = lock.upgradable_read().await;
+                    if *value < 100 {
+                        let mut value = value.upgrade().await;
+                        tokio::task::yield_now().await;
+                        *value += 1;
+                    }
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(Arc::try_unwrap(lock).ok().unwrap().into_inner(), 100);
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::Mutex;
+
+/// Elemento que circula por el buffer, identificado por su productor y su número de secuencia.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Item {
+    pub producer: i32,  // Productor que lo generó
+    pub sequence: i32,  // Posición en la secuencia del productor, desde 1
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "P{}#{}", self.producer, self.sequence)
+    }
+}
+
+/// Registro de lo ocurrido con cada elemento.
+#[derive(Default)]
+struct AuditState {
+    produced: BTreeSet<Item>,              // Elementos que entraron al buffer
+    rejected: BTreeSet<Item>,              // Elementos que no entraron porque el buffer se cerró
+    consumed: BTreeMap<Item, u32>,         // Veces que se consumió cada elemento
+    left_over: BTreeSet<Item>,             // Elementos que quedaron en el buffer al terminar
+    reordered: Vec<(i32, Item, Item)>,     // (consumidor, anterior, siguiente) fuera de orden
+    unmet_quotas: Vec<(i32, i32)>,         // (consumidor, elementos que le faltaron)
+}
+
+/// Seguimiento de cada elemento desde que se produce hasta que se consume.
+///
+/// Un consumidor saca elementos del buffer uno detrás de otro y el buffer es FIFO, así que
+/// los elementos de un mismo productor deben llegarle en orden creciente de secuencia.
+/// Cada consumidor lo comprueba con lo que él mismo ve, sin depender del orden en que los
+/// distintos hilos registran sus operaciones.
+pub struct Audit {
+    state: Mutex<AuditState>,
+}
+
+impl Audit {
+    /// Crea un registro vacío.
+    pub fn new() -> Self {
+        Audit { state: Mutex::new(AuditState::default()) }
+    }
+
+    /// Registra que un elemento entró al buffer.
+    pub fn produced(&self, item: Item) {
+        self.state.lock().unwrap().produced.insert(item);
+    }
+
+    /// Registra que un elemento no pudo entrar porque el buffer estaba cerrado.
+    pub fn rejected(&self, item: Item) {
+        self.state.lock().unwrap().rejected.insert(item);
+    }
+
+    /// Registra que un consumidor sacó un elemento.
+    ///
+    /// # Parámetros
+    /// - `consumer`: identificador del consumidor.
+    /// - `previous`: último elemento del mismo productor que vio este consumidor, si hubo.
+    /// - `item`: elemento consumido.
+    pub fn consumed(&self, consumer: i32, previous: Option<Item>, item: Item) {
+        let mut state = self.state.lock().unwrap();
+        *state.consumed.entry(item).or_insert(0) += 1;
+        if let Some(previous) = previous {
+            if previous.sequence >= item.sequence {
+                state.reordered.push((consumer, previous, item));
+            }
+        }
+    }
+
+    /// Registra un elemento que quedó en el buffer cuando ya no había consumidores.
+    pub fn left_over(&self, item: Item) {
+        self.state.lock().unwrap().left_over.insert(item);
+    }
+
+    /// Registra que un consumidor terminó sin llegar a su cuota porque el buffer se cerró.
+    pub fn unmet_quota(&self, consumer: i32, missing: i32) {
+        self.state.lock().unwrap().unmet_quotas.push((consumer, missing));
+    }
+
+    /// Compara lo producido con lo consumido.
+    ///
+    /// # Retorno
+    /// Retorna un `Summary` con los totales y cada anomalía encontrada.
+    pub fn summary(&self) -> Summary {
+        let state = self.state.lock().unwrap();
+        Summary {
+            produced: state.produced.len(),
+            consumed: state.consumed.values().sum(),
+            rejected: state.rejected.len(),
+            left_over: state.left_over.len(),
+            unmet_quotas: state.unmet_quotas.clone(),
+            lost: state
+                .produced
+                .iter()
+                .filter(|item| !state.consumed.contains_key(item) && !state.left_over.contains(item))
+                .copied()
+                .collect(),
+            duplicated: state.consumed.iter().filter(|(_, count)| **count > 1).map(|(item, count)| (*item, *count)).collect(),
+            unknown: state.consumed.keys().filter(|item| !state.produced.contains(item)).copied().collect(),
+            reordered: state.reordered.clone(),
+        }
+    }
+}
+
+/// Resultado de la auditoría.
+///
+/// No incluye si el buffer superó su capacidad: `BoundedBuffer` no deja agregar elementos
+/// cuando está lleno, así que eso no puede pasar. La ocupación máxima se muestra solo como dato.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub produced: usize,                   // Elementos que entraron al buffer
+    pub consumed: u32,                     // Consumos registrados, contando los repetidos
+    pub rejected: usize,                   // Elementos que no entraron porque el buffer se cerró
+    pub left_over: usize,                  // Elementos que quedaron en el buffer al terminar
+    pub unmet_quotas: Vec<(i32, i32)>,     // (consumidor, elementos que le faltaron)
+    pub lost: Vec<Item>,                   // Producidos que nadie consumió ni quedaron en el buffer
+    pub duplicated: Vec<(Item, u32)>,      // Elementos consumidos más de una vez, con las veces
+    pub unknown: Vec<Item>,                // Consumidos que nunca se produjeron
+    pub reordered: Vec<(i32, Item, Item)>, // (consumidor, anterior, siguiente) fuera de orden
+}
+
+impl Summary {
+    /// Retorna `true` si no se perdió, duplicó, inventó ni desordenó ningún elemento.
+    pub fn is_correct(&self) -> bool {
+        self.lost.is_empty() && self.duplicated.is_empty() && self.unknown.is_empty() && self.reordered.is_empty()
+    }
+
+    /// Imprime el resumen de la auditoría.
+    ///
+    /// # Parámetros
+    /// - `high_water_mark`: máxima ocupación que alcanzó el buffer.
+    /// - `capacity`: capacidad del buffer.
+    pub fn print(&self, high_water_mark: usize, capacity: usize) {
+        let duplicated: Vec<String> = self.duplicated.iter().map(|(item, count)| format!("{} x{}", item, count)).collect();
+        let reordered: Vec<String> = self
+            .reordered
+            .iter()
+            .map(|(consumer, previous, item)| format!("C{}: {} antes que {}", consumer, previous, item))
+            .collect();
+
+        println!("===== Auditoría del buffer =====");
+        println!("{:<30} {}", "Producidos:", self.produced);
+        println!("{:<30} {}", "Consumidos:", self.consumed);
+        println!("{:<30} {}", "Rechazados (buffer cerrado):", self.rejected);
+        println!("{:<30} {}", "Sin consumir en el buffer:", self.left_over);
+        for (consumer, missing) in &self.unmet_quotas {
+            println!("Al consumidor {} le faltaron {} elementos de su cuota.", consumer, missing);
+        }
+        println!("{:<30} {} de {} (el buffer no admite más)", "Ocupación máxima:", high_water_mark, capacity);
+        println!("{:<30} {}", "Perdidos:", list(&self.lost));
+        println!("{:<30} {}", "Duplicados:", list(&duplicated));
+        println!("{:<30} {}", "Consumidos sin producir:", list(&self.unknown));
+        println!("{:<30} {}", "Fuera de orden:", list(&reordered));
+        println!("Resultado: {}", if self.is_correct() { "correcto" } else { "INCORRECTO" });
+    }
+}
+
+/// Escribe una lista de valores separados por comas, o "ninguno" si está vacía.
+fn list<T: fmt::Display>(values: &[T]) -> String {
+    if values.is_empty() {
+        return "ninguno".to_string();
+    }
+    values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(producer: i32, sequence: i32) -> Item {
+        Item { producer, sequence }
+    }
+
+    #[test]
+    fn a_clean_run_is_correct() {
+        let audit = Audit::new();
+        audit.produced(item(1, 1));
+        audit.produced(item(1, 2));
+        audit.consumed(1, None, item(1, 1));
+        audit.consumed(1, Some(item(1, 1)), item(1, 2));
+        let summary = audit.summary();
+        assert!(summary.is_correct());
+        assert_eq!((summary.produced, summary.consumed), (2, 2));
+    }
+
+    #[test]
+    fn detects_lost_items_but_not_the_ones_left_in_the_buffer() {
+        let audit = Audit::new();
+        for sequence in 1..=3 {
+            audit.produced(item(1, sequence));
+        }
+        audit.consumed(1, None, item(1, 1));
+        audit.left_over(item(1, 3));
+        let summary = audit.summary();
+        assert_eq!(summary.lost, [item(1, 2)]);
+        assert!(!summary.is_correct());
+    }
+
+    #[test]
+    fn detects_duplicated_and_unknown_items() {
+        let audit = Audit::new();
+        audit.produced(item(1, 1));
+        audit.consumed(1, None, item(1, 1));
+        audit.consumed(2, None, item(1, 1));
+        audit.consumed(2, Some(item(1, 1)), item(1, 2));
+        let summary = audit.summary();
+        assert_eq!(summary.duplicated, [(item(1, 1), 2)]);
+        assert_eq!(summary.unknown, [item(1, 2)]);
+        assert_eq!(summary.consumed, 3);
+    }
+
+    #[test]
+    fn detects_items_of_one_producer_seen_out_of_order() {
+        let audit = Audit::new();
+        audit.produced(item(1, 1));
+        audit.produced(item(1, 2));
+        audit.produced(item(2, 1));
+        audit.consumed(1, None, item(1, 2));
+        // Otro productor en medio no es desorden.
+        audit.consumed(1, None, item(2, 1));
+        audit.consumed(1, Some(item(1, 2)), item(1, 1));
+        let summary = audit.summary();
+        assert_eq!(summary.reordered, [(1, item(1, 2), item(1, 1))]);
+        assert!(!summary.is_correct());
+    }
+}
@@ -1,36 +1,208 @@
-pub struct Buffer {
-    pub data: Vec<i32>,
-    pub capacity: usize,
+use std::collections::VecDeque;
+use std::time::Duration;
+use crate::monitor::{Monitor, Queue, Signal};
+
+/// Error de `put_timeout`. Devuelve el elemento que no se pudo agregar.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PutError<T> {
+    Closed(T),   // El buffer se cerró
+    Timeout(T),  // No hubo espacio a tiempo
+}
+
+/// Error de `take_timeout`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TakeError {
+    Closed,   // El buffer se cerró y ya no quedan elementos
+    Timeout,  // No hubo elementos a tiempo
+}
+
+/// Estado interno del buffer, protegido por el monitor.
+struct BufferState<T> {
+    items: VecDeque<T>,  // Elementos en orden de llegada
+    closed: bool,        // Si ya no se aceptan elementos nuevos
+    high_water: usize,   // Máxima cantidad de elementos que llegó a tener
 }
 
-impl Buffer {
+/// Cola donde esperan los productores a que haya espacio.
+const NOT_FULL: Queue = 0;
+/// Cola donde esperan los consumidores a que haya elementos.
+const NOT_EMPTY: Queue = 1;
+
+/// Buffer acotado compartido por varios productores y consumidores.
+pub struct BoundedBuffer<T> {
+    monitor: Monitor<BufferState<T>>,  // Elementos y bandera de cierre, con sus colas `NOT_FULL` y `NOT_EMPTY`
+    capacity: usize,                   // Capacidad máxima del buffer
+}
+
+impl<T> BoundedBuffer<T> {
     /// Crea un nuevo buffer con una capacidad definida.
-    /// 
+    ///
     /// # Parámetros
-    /// - `capacity`: capacidad máxima del buffer.
-    /// 
+    /// - `capacity`: capacidad máxima del buffer. Debe ser al menos 1.
+    ///
     /// # Retorno
-    /// Retorna una instancia de la estructura `Buffer` inicializada con un vector vacío y la capacidad especificada.
+    /// Retorna un `BoundedBuffer` vacío y abierto.
     pub fn new(capacity: usize) -> Self {
-        Buffer {
-            data: Vec::new(),
+        assert!(capacity > 0, "la capacidad del buffer debe ser al menos 1");
+        BoundedBuffer {
+            monitor: Monitor::new(BufferState {
+                items: VecDeque::with_capacity(capacity),
+                closed: false,
+                high_water: 0,
+            }, 2),
             capacity,
         }
     }
 
-    /// Verifica si el buffer está lleno.
-    /// 
+    /// Agrega un elemento, esperando hasta que haya espacio.
+    ///
+    /// # Parámetros
+    /// - `item`: elemento a agregar.
+    ///
     /// # Retorno
-    /// Retorna `true` si la cantidad de elementos en el buffer es igual a su capacidad máxima, de lo contrario retorna `false`.
-    pub fn is_full(&self) -> bool {
-        self.data.len() == self.capacity
+    /// Retorna `Err` con el elemento si el buffer está cerrado.
+    pub fn put(&self, item: T) -> Result<(), T> {
+        let capacity = self.capacity;
+        self.monitor.wait_until(
+            NOT_FULL,
+            |state| state.closed || state.items.len() < capacity,
+            |state| {
+                if state.closed {
+                    return (Err(item), Signal::Nobody);
+                }
+                state.items.push_back(item);
+                state.high_water = state.high_water.max(state.items.len());
+                (Ok(()), Signal::One(NOT_EMPTY))
+            },
+        )
     }
 
-    /// Verifica si el buffer está vacío.
-    /// 
+    /// Agrega un elemento, esperando como mucho `timeout` a que haya espacio.
+    ///
+    /// # Parámetros
+    /// - `item`: elemento a agregar.
+    /// - `timeout`: tiempo máximo de espera.
+    ///
     /// # Retorno
-    /// Retorna `true` si no hay elementos en el buffer, de lo contrario retorna `false`.
-    pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+    /// Retorna `PutError` con el elemento si el buffer está cerrado o no hubo espacio a tiempo.
+    pub fn put_timeout(&self, item: T, timeout: Duration) -> Result<(), PutError<T>> {
+        let capacity = self.capacity;
+        let mut item = Some(item);
+        let result = self.monitor.wait_until_timeout(
+            NOT_FULL,
+            timeout,
+            |state| state.closed || state.items.len() < capacity,
+            |state| {
+                let item = item.take().unwrap();
+                if state.closed {
+                    return (Err(PutError::Closed(item)), Signal::Nobody);
+                }
+                state.items.push_back(item);
+                state.high_water = state.high_water.max(state.items.len());
+                (Ok(()), Signal::One(NOT_EMPTY))
+            },
+        );
+        result.unwrap_or_else(|| Err(PutError::Timeout(item.take().unwrap())))
+    }
+
+    /// Saca el elemento más antiguo, esperando hasta que haya uno.
+    ///
+    /// # Retorno
+    /// Retorna `None` cuando el buffer está cerrado y vacío: no llegarán más elementos.
+    pub fn take(&self) -> Option<T> {
+        self.monitor.wait_until(NOT_EMPTY, |state| state.closed || !state.items.is_empty(), take_front)
+    }
+
+    /// Saca el elemento más antiguo, esperando como mucho `timeout` a que haya uno.
+    ///
+    /// # Parámetros
+    /// - `timeout`: tiempo máximo de espera.
+    ///
+    /// # Retorno
+    /// Retorna `TakeError` si el buffer está cerrado y vacío o no hubo elementos a tiempo.
+    pub fn take_timeout(&self, timeout: Duration) -> Result<T, TakeError> {
+        self.monitor
+            .wait_until_timeout(NOT_EMPTY, timeout, |state| state.closed || !state.items.is_empty(), take_front)
+            .map_or(Err(TakeError::Timeout), |item| item.ok_or(TakeError::Closed))
+    }
+
+    /// Retorna la máxima cantidad de elementos que llegó a tener el buffer.
+    pub fn high_water_mark(&self) -> usize {
+        self.monitor.with(|state| state.high_water)
     }
-}
\ No newline at end of file
+
+    /// Cierra el buffer. Los productores ya no pueden agregar elementos y los consumidores
+    /// terminan cuando sacan los que quedan. Despierta a todos los hilos que esperan.
+    pub fn close(&self) {
+        self.monitor.update(|state| {
+            state.closed = true;
+            ((), Signal::Everyone)
+        });
+    }
+}
+
+/// Saca el elemento más antiguo y, si sacó uno, despierta a un productor que espera espacio.
+fn take_front<T>(state: &mut BufferState<T>) -> (Option<T>, Signal) {
+    match state.items.pop_front() {
+        Some(item) => (Some(item), Signal::One(NOT_FULL)),
+        None => (None, Signal::Nobody),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn items_come_out_in_arrival_order() {
+        let buffer = BoundedBuffer::new(3);
+        for item in 1..=3 {
+            buffer.put(item).unwrap();
+        }
+        assert_eq!((buffer.take(), buffer.take(), buffer.take()), (Some(1), Some(2), Some(3)));
+        assert_eq!(buffer.high_water_mark(), 3);
+    }
+
+    #[test]
+    fn a_full_buffer_blocks_the_producer_until_there_is_room() {
+        let buffer = Arc::new(BoundedBuffer::new(1));
+        buffer.put(1).unwrap();
+        assert_eq!(buffer.put_timeout(2, Duration::from_millis(20)), Err(PutError::Timeout(2)));
+
+        let producer = thread::spawn({
+            let buffer = Arc::clone(&buffer);
+            move || buffer.put(2)
+        });
+        assert_eq!(buffer.take(), Some(1));
+        producer.join().unwrap().unwrap();
+        assert_eq!(buffer.take(), Some(2));
+        assert_eq!(buffer.high_water_mark(), 1);
+    }
+
+    #[test]
+    fn closing_wakes_consumers_and_rejects_producers() {
+        let buffer = Arc::new(BoundedBuffer::<i32>::new(1));
+        assert_eq!(buffer.take_timeout(Duration::from_millis(20)), Err(TakeError::Timeout));
+
+        let consumer = thread::spawn({
+            let buffer = Arc::clone(&buffer);
+            move || buffer.take()
+        });
+        buffer.close();
+        assert_eq!(consumer.join().unwrap(), None);
+        assert_eq!(buffer.put(1), Err(1));
+        assert_eq!(buffer.put_timeout(2, Duration::from_millis(20)), Err(PutError::Closed(2)));
+        assert_eq!(buffer.take_timeout(Duration::from_millis(20)), Err(TakeError::Closed));
+    }
+
+    #[test]
+    fn items_put_before_closing_are_still_taken() {
+        let buffer = BoundedBuffer::new(2);
+        buffer.put(1).unwrap();
+        buffer.close();
+        assert_eq!(buffer.take(), Some(1));
+        assert_eq!(buffer.take(), None);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+use crate::buffer::BoundedBuffer;
+
+/// Canal acotado entre productores y consumidores. Las operaciones son asíncronas para que
+/// el mismo código sirva a los dos modelos; quién ejecuta ese código y cómo espera lo decide
+/// el `Runtime` al que pertenece el canal.
+pub trait Channel<T: Send + 'static>: Clone + Send + Sync + 'static {
+    /// Envía un mensaje, esperando hasta que haya espacio.
+    ///
+    /// # Retorno
+    /// Retorna `Err` con el mensaje si el canal está cerrado.
+    fn send(&self, message: T) -> impl Future<Output = Result<(), T>> + Send;
+
+    /// Recibe el mensaje más antiguo, esperando hasta que haya uno.
+    ///
+    /// # Retorno
+    /// Retorna `None` cuando el canal está cerrado y vacío.
+    fn recv(&self) -> impl Future<Output = Option<T>> + Send;
+
+    /// Cierra el canal: no se aceptan más mensajes y los receptores terminan al vaciarlo.
+    fn close(&self);
+}
+
+/// Canal del backend de hilos: un `BoundedBuffer`, el monitor bloqueante.
+pub struct ThreadChannel<T> {
+    buffer: Arc<BoundedBuffer<T>>,
+}
+
+impl<T> Clone for ThreadChannel<T> {
+    fn clone(&self) -> Self {
+        ThreadChannel { buffer: Arc::clone(&self.buffer) }
+    }
+}
+
+impl<T> ThreadChannel<T> {
+    /// Crea un canal vacío y abierto.
+    ///
+    /// # Parámetros
+    /// - `capacity`: número máximo de mensajes en el canal.
+    pub fn new(capacity: usize) -> Self {
+        ThreadChannel { buffer: Arc::new(BoundedBuffer::new(capacity)) }
+    }
+}
+
+/// Las operaciones esperan bloqueando el hilo que las llama, nunca suspendiendo la tarea:
+/// solo sirven dentro de `ThreadRuntime`, donde cada tarea tiene su propio hilo.
+impl<T: Send + 'static> Channel<T> for ThreadChannel<T> {
+    async fn send(&self, message: T) -> Result<(), T> {
+        self.buffer.put(message)
+    }
+
+    async fn recv(&self) -> Option<T> {
+        self.buffer.take()
+    }
+
+    fn close(&self) {
+        self.buffer.close();
+    }
+}
+
+/// Canal del backend de tokio: una cola protegida por un mutex y dos semáforos asíncronos,
+/// `empty_slots` y `full_slots`, como en el productor/consumidor del ejercicio 1.
+pub struct TokioChannel<T> {
+    items: Arc<Mutex<VecDeque<T>>>,  // Mensajes en orden de llegada
+    empty_slots: Arc<Semaphore>,     // Un permiso por espacio libre
+    full_slots: Arc<Semaphore>,      // Un permiso por mensaje, más uno al cerrar
+    closed: Arc<AtomicBool>,         // Si ya no se aceptan mensajes
+}
+
+impl<T> Clone for TokioChannel<T> {
+    fn clone(&self) -> Self {
+        TokioChannel {
+            items: Arc::clone(&self.items),
+            empty_slots: Arc::clone(&self.empty_slots),
+            full_slots: Arc::clone(&self.full_slots),
+            closed: Arc::clone(&self.closed),
+        }
+    }
+}
+
+impl<T> TokioChannel<T> {
+    /// Crea un canal vacío y abierto.
+    ///
+    /// # Parámetros
+    /// - `capacity`: número máximo de mensajes en el canal.
+    pub fn new(capacity: usize) -> Self {
+        TokioChannel {
+            items: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            empty_slots: Arc::new(Semaphore::new(capacity)),
+            full_slots: Arc::new(Semaphore::new(0)),
+            closed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl<T: Send + 'static> Channel<T> for TokioChannel<T> {
+    async fn send(&self, message: T) -> Result<(), T> {
+        // El semáforo cerrado despierta a los productores que esperan espacio.
+        match self.empty_slots.acquire().await {
+            Ok(permit) => permit.forget(),
+            Err(_) => return Err(message),
+        }
+        {
+            let mut items = self.items.lock().unwrap();
+            if self.closed.load(Ordering::SeqCst) {
+                return Err(message);
+            }
+            items.push_back(message);
+        }
+        self.full_slots.add_permits(1);
+        Ok(())
+    }
+
+    async fn recv(&self) -> Option<T> {
+        self.full_slots.acquire().await.unwrap().forget();
+        let message = self.items.lock().unwrap().pop_front();
+        match message {
+            Some(message) => {
+                self.empty_slots.add_permits(1);
+                Some(message)
+            }
+            None => {
+                // Era el permiso de cierre: se devuelve para despertar al siguiente receptor.
+                self.full_slots.add_permits(1);
+                None
+            }
+        }
+    }
+
+    fn close(&self) {
+        let _items = self.items.lock().unwrap();
+        if !self.closed.swap(true, Ordering::SeqCst) {
+            self.empty_slots.close();
+            self.full_slots.add_permits(1);
+        }
+    }
+}
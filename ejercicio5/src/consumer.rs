@@ -1,10 +1,16 @@
-use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
-use crate::buffer::Buffer;
+use crate::audit::{Audit, Item};
+use crate::buffer::{BoundedBuffer, TakeError};
 
 pub struct Consumer {
-    buffer: Arc<Mutex<Buffer>>,
+    id: i32,
+    buffer: Arc<BoundedBuffer<Item>>,
+    audit: Arc<Audit>,
+    active_consumers: Arc<AtomicI32>,
     items_to_consume: i32,
 }
 
@@ -12,42 +18,67 @@ impl Consumer {
     /// Crea una nueva instancia de la clase `Consumer`.
     /// 
     /// # Parámetros
-    /// - `buffer`: un `Arc<Mutex<Buffer>>` que apunta al buffer compartido.
-    /// - `items_to_consume`: número de elementos que el productor generará.
+    /// - `id`: identificador del consumidor.
+    /// - `buffer`: un `Arc<BoundedBuffer<Item>>` que apunta al buffer compartido.
+    /// - `audit`: registro compartido donde se anota cada elemento consumido.
+    /// - `active_consumers`: consumidores que siguen trabajando; el último en terminar cierra el buffer.
+    /// - `items_to_consume`: número de elementos que el consumidor quiere consumir.
     /// 
     /// # Retorno
     /// Retorna una nueva instancia de `Consumer` asociada al buffer compartido.
-    pub fn new(buffer: Arc<Mutex<Buffer>>, items_to_consume: i32) -> Self {
-        Consumer { buffer, items_to_consume }
+    pub fn new(
+        id: i32,
+        buffer: Arc<BoundedBuffer<Item>>,
+        audit: Arc<Audit>,
+        active_consumers: Arc<AtomicI32>,
+        items_to_consume: i32,
+    ) -> Self {
+        Consumer { id, buffer, audit, active_consumers, items_to_consume }
     }
 
-    /// Ejecuta el consumidor en un hilo separado.
+    /// Ejecuta el consumidor en un hilo separado. Consume hasta completar su cuota o hasta
+    /// que el buffer se cierra y se vacía.
     /// 
     /// @Arc::clone() se debe clonar para no mover toda la propiedad lo que lo haria inaccesible
-    /// @lock().unwrap() espera hasta que el buffer tenga al menos un elemento y continua
-    /// @remove() eliminar el primer valor del buffer liberando espacio para que el productor pueda seguir agregando nuevos elementos
+    /// @take_timeout() espera hasta un segundo a que el buffer tenga un elemento; si no lo hay avisa y sigue esperando con take()
+    /// @close() el último consumidor en terminar cierra el buffer para que los productores no esperen espacio para siempre
     /// @sleep() simula tiempo
     /// 
     /// # Retorno
     /// Retorna un `JoinHandle<()>` que representa el hilo en el cual el consumidor está ejecutándose.
     pub fn run(&self) -> thread::JoinHandle<()> {
         let buffer_clone = Arc::clone(&self.buffer);
+        let audit = Arc::clone(&self.audit);
+        let active_consumers = Arc::clone(&self.active_consumers);
+        let id = self.id;
         let items = self.items_to_consume;
         thread::spawn(move || {
-            for _ in 1..=items {
-                loop {
-                    {
-                        let mut buf = buffer_clone.lock().unwrap();
-                        if !buf.is_empty() {
-                            let value: i32 = buf.data.remove(0);
-                            println!("Consumió: {}", value);
-                            break;
-                        }
+            // Último elemento que este consumidor vio de cada productor
+            let mut last_seen: HashMap<i32, Item> = HashMap::new();
+
+            for consumed in 0..items {
+                let item = match buffer_clone.take_timeout(Duration::from_secs(1)) {
+                    Ok(item) => Some(item),
+                    Err(TakeError::Timeout) => {
+                        println!("Consumidor {} sigue esperando un elemento...", id);
+                        buffer_clone.take()
                     }
-                    thread::sleep(Duration::from_millis(50));
-                }
+                    Err(TakeError::Closed) => None,
+                };
+                let Some(item) = item else {
+                    println!("Consumidor {} terminó: el buffer está cerrado y vacío.", id);
+                    audit.unmet_quota(id, items - consumed);
+                    break;
+                };
+                audit.consumed(id, last_seen.insert(item.producer, item), item);
+                println!("Consumidor {} consumió: {}", id, item);
                 thread::sleep(Duration::from_millis(400));
             }
+
+            if active_consumers.fetch_sub(1, Ordering::SeqCst) == 1 {
+                println!("Consumidor {} fue el último en terminar: cierra el buffer.", id);
+                buffer_clone.close();
+            }
         })
     }
 }
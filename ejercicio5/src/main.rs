@@ -1,8 +1,12 @@
+mod audit;
 mod buffer;
+mod channel;
 mod producer;
 mod consumer;
 mod menu;
 mod monitor;
+mod runtime;
+mod workload;
 
 use std::io;
 
@@ -14,6 +18,7 @@ fn main() {
         println!("Elija una opción:");
         println!("1. Problema 14");
         println!("2. Problema 15");
+        println!("3. Problema 14: comparar hilos y tokio");
         println!("0. Salir");
 
         answer.clear();
@@ -24,6 +29,7 @@ fn main() {
         match answer.trim() {
             "1" => menu::problema_14(),
             "2" => menu::problema_15(),
+            "3" => menu::problema_14_backends(),
             "0" => {
                 println!("Hasta luego.");
                 break;
@@ -1,15 +1,21 @@
-use std::sync::{Arc, Mutex};
+use std::io::{self, Write};
+use std::sync::atomic::AtomicI32;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 
-use crate::buffer::Buffer;
+use crate::audit::{Audit, Summary};
+use crate::buffer::BoundedBuffer;
 use crate::producer::Producer;
 use crate::consumer::Consumer;
 use crate::monitor::ReaderWriterMonitor;
+use crate::workload::compare_backends;
 
 const BUFFER_SIZE: usize = 5;
 const PRODCUER_SIZE: i32 = 10;
 const CONSUMER_SIZE: i32 = 10;
+const PRODUCER_COUNT: i32 = 2;
+const CONSUMER_COUNT: i32 = 2;
 const READER_SIZE: i32 = 5;
 const WRITER_SIZE: i32 = 5;
 
@@ -18,20 +24,92 @@ const WRITER_SIZE: i32 = 5;
 /// Para este problema se tiene un monito 
 /// para los lectores y escritores
 pub fn problema_14() {
-        // Crear el buffer compartido
-        let buffer = Arc::new(Mutex::new(Buffer::new(BUFFER_SIZE)));
-
-        // Crear el productor y el consumidor
-        let producer = Producer::new(Arc::clone(&buffer), PRODCUER_SIZE);
-        let consumer = Consumer::new(Arc::clone(&buffer), CONSUMER_SIZE);
-    
-        // Ejecutar productor y consumidor
-        let producer_handle = producer.run();
-        let consumer_handle = consumer.run();
-    
-        // Esperar a que ambos hilos terminen
-        producer_handle.join().unwrap();
-        consumer_handle.join().unwrap();
+        // Las cantidades pueden no coincidir: la auditoría muestra qué pasó con cada elemento
+        let items_to_produce = read_number("Elementos por productor", PRODCUER_SIZE);
+        let items_to_consume = read_number("Elementos por consumidor", CONSUMER_SIZE);
+
+        let (summary, high_water_mark) =
+            run_audited(BUFFER_SIZE, PRODUCER_COUNT, CONSUMER_COUNT, items_to_produce, items_to_consume);
+        summary.print(high_water_mark, BUFFER_SIZE);
+}
+
+/// Ejecuta productores y consumidores sobre un buffer compartido y audita cada elemento.
+///
+/// # Parámetros
+/// - `capacity`: capacidad del buffer.
+/// - `producer_count`: número de productores.
+/// - `consumer_count`: número de consumidores.
+/// - `items_to_produce`: elementos que genera cada productor.
+/// - `items_to_consume`: elementos que quiere consumir cada consumidor.
+///
+/// # Retorno
+/// Retorna el resumen de la auditoría y la máxima ocupación que alcanzó el buffer.
+fn run_audited(
+    capacity: usize,
+    producer_count: i32,
+    consumer_count: i32,
+    items_to_produce: i32,
+    items_to_consume: i32,
+) -> (Summary, usize) {
+    // Crear el buffer compartido y el registro de auditoría
+    let buffer = Arc::new(BoundedBuffer::new(capacity));
+    let audit = Arc::new(Audit::new());
+    let active_producers = Arc::new(AtomicI32::new(producer_count));
+    let active_consumers = Arc::new(AtomicI32::new(consumer_count));
+
+    // Crear y ejecutar los productores y los consumidores
+    let producer_handles: Vec<_> = (1..=producer_count)
+        .map(|id| {
+            Producer::new(id, Arc::clone(&buffer), Arc::clone(&audit), Arc::clone(&active_producers), items_to_produce)
+                .run()
+        })
+        .collect();
+    let consumer_handles: Vec<_> = (1..=consumer_count)
+        .map(|id| {
+            Consumer::new(id, Arc::clone(&buffer), Arc::clone(&audit), Arc::clone(&active_consumers), items_to_consume)
+                .run()
+        })
+        .collect();
+
+    // El último productor en terminar cierra el buffer y los consumidores terminan al vaciarlo;
+    // si terminan antes los consumidores, el último lo cierra y los productores dejan de esperar
+    for handle in producer_handles.into_iter().chain(consumer_handles) {
+        handle.join().unwrap();
+    }
+
+    // Lo que queda en el buffer ya no tiene quién lo consuma
+    while let Some(item) = buffer.take() {
+        audit.left_over(item);
+    }
+    (audit.summary(), buffer.high_water_mark())
+}
+
+/// Pide un número al usuario, usando el valor por defecto si la respuesta está vacía o no es válida.
+/// 
+/// # Parámetros
+/// - `label`: texto que se muestra al usuario.
+/// - `default`: valor usado si el usuario solo presiona Enter.
+fn read_number(label: &str, default: i32) -> i32 {
+    print!("{} [{}]: ", label, default);
+    io::stdout().flush().unwrap();
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).unwrap_or(0);
+    match answer.trim() {
+        "" => default,
+        text => text.parse().unwrap_or_else(|_| {
+            println!("Valor inválido, se usa {}.", default);
+            default
+        }),
+    }
+}
+
+/// Funcion del ejercicio 14 comparando backends
+/// 
+/// Ejecuta los mismos escenarios de productores y consumidores
+/// con hilos del sistema y con tareas de tokio
+pub fn problema_14_backends() {
+    compare_backends();
 }
 
 /// Funcion del ejercicio 15
@@ -46,11 +124,10 @@ pub fn problema_15() {
     for i in 1..=READER_SIZE {
         let monitor_clone = Arc::clone(&monitor);
         handles.push(thread::spawn(move || {
-            monitor_clone.start_read();
+            let _lectura = monitor_clone.read();
             println!("Lector {} está leyendo...", i);
             thread::sleep(Duration::from_secs(1));
             println!("Lector {} terminó de leer.", i);
-            monitor_clone.end_read();
         }));
     }
 
@@ -58,11 +135,10 @@ pub fn problema_15() {
     for i in 1..=WRITER_SIZE {
         let monitor_clone = Arc::clone(&monitor);
         handles.push(thread::spawn(move || {
-            monitor_clone.start_write();
+            let _escritura = monitor_clone.write();
             println!("Escritor {} está escribiendo...", i);
             thread::sleep(Duration::from_secs(2));
             println!("Escritor {} terminó de escribir.", i);
-            monitor_clone.end_write();
         }));
     }
 
@@ -70,4 +146,30 @@ pub fn problema_15() {
     for handle in handles {
         handle.join().unwrap();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn more_consumption_than_production_ends_with_unmet_quotas() {
+        // 4 elementos producidos para 6 pedidos: el último productor cierra el buffer.
+        let (summary, high_water_mark) = run_audited(2, 2, 2, 2, 3);
+        assert!(summary.is_correct());
+        assert_eq!((summary.produced, summary.consumed, summary.rejected), (4, 4, 0));
+        assert_eq!(summary.unmet_quotas.iter().map(|(_, missing)| missing).sum::<i32>(), 2);
+        assert!(high_water_mark <= 2);
+    }
+
+    #[test]
+    fn more_production_than_consumption_ends_with_rejected_items() {
+        // 6 elementos producidos para 2 pedidos: el último consumidor cierra el buffer.
+        let (summary, high_water_mark) = run_audited(1, 2, 2, 3, 1);
+        assert!(summary.is_correct());
+        assert_eq!(summary.consumed, 2);
+        assert_eq!(summary.produced + summary.rejected, 6);
+        assert_eq!(summary.produced, 2 + summary.left_over);
+        assert!(summary.unmet_quotas.is_empty());
+        assert_eq!(high_water_mark, 1);
+    }
+}
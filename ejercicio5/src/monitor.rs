@@ -1,10 +1,172 @@
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
 use std::time::Duration;
 
+/// Cola de espera de un monitor. Cada cola tiene su propia variable de condición, así un
+/// cambio de estado despierta solo a los hilos que pueden aprovecharlo.
+pub type Queue = usize;
+
+/// A quién despertar después de cambiar el estado.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signal {
+    Nobody,      // El cambio no habilita a ningún hilo que espera
+    One(Queue),  // Un hilo de la cola
+    All(Queue),  // Todos los hilos de la cola
+    Everyone,    // Todos los hilos de todas las colas
+}
+
+/// Monitor genérico: un estado `S` protegido por un mutex y una variable de condición por
+/// cola de espera.
+///
+/// Las operaciones que cambian el estado indican a quién despertar; las lecturas no
+/// despiertan a nadie. Todas las esperas comprueban su condición con el mutex tomado y
+/// vuelven a dormir si no se cumple, así que una notificación de más nunca es un error,
+/// solo trabajo extra.
+pub struct Monitor<S> {
+    state: Mutex<S>,      // Estado compartido
+    queues: Vec<Condvar>, // Una variable de condición por cola de espera
+}
+
+impl<S> Monitor<S> {
+    /// Crea un nuevo monitor que protege el estado indicado.
+    ///
+    /// # Parámetros
+    /// - `state`: estado inicial.
+    /// - `queues`: número de colas de espera; las colas van de 0 a `queues - 1`.
+    ///
+    /// # Retorno
+    /// Retorna una instancia de `Monitor` sin hilos esperando.
+    pub fn new(state: S, queues: usize) -> Self {
+        assert!(queues > 0, "el monitor necesita al menos una cola de espera");
+        Monitor {
+            state: Mutex::new(state),
+            queues: (0..queues).map(|_| Condvar::new()).collect(),
+        }
+    }
+
+    /// Bloquea el estado. Un hilo que entra en pánico dentro de una operación deja el
+    /// mutex envenenado, pero las operaciones del monitor no dejan cambios a medias.
+    fn lock(&self) -> MutexGuard<'_, S> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Despierta a los hilos indicados por `signal`.
+    fn signal(&self, signal: Signal) {
+        match signal {
+            Signal::Nobody => {}
+            Signal::One(queue) => self.queues[queue].notify_one(),
+            Signal::All(queue) => self.queues[queue].notify_all(),
+            Signal::Everyone => self.queues.iter().for_each(Condvar::notify_all),
+        }
+    }
+
+    /// Lee el estado sin cambiarlo, así que no despierta a nadie.
+    ///
+    /// # Parámetros
+    /// - `action`: consulta sobre el estado.
+    ///
+    /// # Retorno
+    /// Retorna el resultado de `action`.
+    pub fn with<R>(&self, action: impl FnOnce(&S) -> R) -> R {
+        action(&self.lock())
+    }
+
+    /// Ejecuta `action` con acceso exclusivo al estado y despierta a quien indique.
+    ///
+    /// # Parámetros
+    /// - `action`: operación sobre el estado. Retorna su resultado y a quién despertar.
+    ///
+    /// # Retorno
+    /// Retorna el resultado de `action`.
+    pub fn update<R>(&self, action: impl FnOnce(&mut S) -> (R, Signal)) -> R {
+        let (result, signal) = action(&mut self.lock());
+        self.signal(signal);
+        result
+    }
+
+    /// Espera en `queue` hasta que `condition` se cumpla y entonces ejecuta `action` sin
+    /// soltar el mutex, de modo que nadie puede invalidar la condición entre la comprobación
+    /// y la acción.
+    ///
+    /// # Parámetros
+    /// - `queue`: cola donde espera el hilo; es la que deben despertar quienes habilitan `condition`.
+    /// - `condition`: condición sobre el estado que debe cumplirse.
+    /// - `action`: operación sobre el estado. Retorna su resultado y a quién despertar.
+    ///
+    /// # Retorno
+    /// Retorna el resultado de `action`.
+    pub fn wait_until<R>(
+        &self,
+        queue: Queue,
+        mut condition: impl FnMut(&S) -> bool,
+        action: impl FnOnce(&mut S) -> (R, Signal),
+    ) -> R {
+        let state = self.lock();
+        let mut state = self.queues[queue]
+            .wait_while(state, |state| !condition(state))
+            .unwrap_or_else(PoisonError::into_inner);
+        let (result, signal) = action(&mut state);
+        drop(state);
+        self.signal(signal);
+        result
+    }
+
+    /// Igual que `wait_until`, pero deja de esperar cuando pasa `timeout`.
+    ///
+    /// # Parámetros
+    /// - `queue`: cola donde espera el hilo.
+    /// - `timeout`: tiempo máximo de espera.
+    /// - `condition`: condición sobre el estado que debe cumplirse.
+    /// - `action`: operación sobre el estado. Retorna su resultado y a quién despertar.
+    ///
+    /// # Retorno
+    /// Retorna `Some` con el resultado de `action`, o `None` si la condición no se cumplió a tiempo.
+    pub fn wait_until_timeout<R>(
+        &self,
+        queue: Queue,
+        timeout: Duration,
+        mut condition: impl FnMut(&S) -> bool,
+        action: impl FnOnce(&mut S) -> (R, Signal),
+    ) -> Option<R> {
+        let state = self.lock();
+        let (mut state, wait) = self.queues[queue]
+            .wait_timeout_while(state, timeout, |state| !condition(state))
+            .unwrap_or_else(PoisonError::into_inner);
+        if wait.timed_out() {
+            return None;
+        }
+        let (result, signal) = action(&mut state);
+        drop(state);
+        self.signal(signal);
+        Some(result)
+    }
+}
+
+/// Estado compartido de lectores y escritores.
+struct RwState {
+    reader_count: usize,     // Número de lectores activos
+    writer_active: bool,     // Si un escritor está activo
+    waiting_readers: usize,  // Lectores bloqueados esperando entrar
+    waiting_writers: usize,  // Escritores bloqueados esperando entrar
+    reader_batch: usize,     // Lectores que pueden entrar aunque haya escritores esperando
+}
+
+/// Cola donde esperan los lectores.
+const READERS: Queue = 0;
+/// Cola donde esperan los escritores.
+const WRITERS: Queue = 1;
+
 pub struct ReaderWriterMonitor {
-    reader_count: Mutex<usize>,    // Número de lectores activos
-    writer_active: Mutex<bool>,   // Si un escritor está activo
+    monitor: Monitor<RwState>,  // Estado combinado de lectores y escritores, con sus colas `READERS` y `WRITERS`
+}
+
+/// Acceso de lectura. Al destruirse termina la lectura, incluso si el hilo entra en pánico.
+pub struct ReadGuard<'a> {
+    monitor: &'a ReaderWriterMonitor,
+}
+
+/// Acceso de escritura. Al destruirse termina la escritura, incluso si el hilo entra en pánico.
+pub struct WriteGuard<'a> {
+    monitor: &'a ReaderWriterMonitor,
 }
 
 impl ReaderWriterMonitor {
@@ -15,75 +177,281 @@ impl ReaderWriterMonitor {
     /// - `writer_active` comienza en `false` (ningún escritor está activo).
     pub fn new() -> Self {
         Self {
-            reader_count: Mutex::new(0),
-            writer_active: Mutex::new(false),
+            monitor: Monitor::new(RwState {
+                reader_count: 0,
+                writer_active: false,
+                waiting_readers: 0,
+                waiting_writers: 0,
+                reader_batch: 0,
+            }, 2),
         }
     }
 
     /// Permite que un lector inicie la lectura.
     ///
-    /// Este método asegura que los lectores solo accedan al recurso compartido si no hay escritores activos.
-    /// Si un escritor está activo, el lector espera y reintenta después de un breve tiempo.
-    ///
-    /// - Incrementa el contador de lectores (`reader_count`) si no hay escritores activos.
-    /// - Utiliza espera activa (`thread::sleep`) para manejar la concurrencia.
-    pub fn start_read(&self) {
-        loop {
-            let writer_active = self.writer_active.lock().unwrap();
-            if !*writer_active {
-                // Si no hay escritores activos, incrementar contador de lectores y salir del bucle
-                let mut reader_count = self.reader_count.lock().unwrap();
-                *reader_count += 1;
-                return;
-            }
-            // Esperar brevemente antes de reintentar
-            drop(writer_active);
-            thread::sleep(Duration::from_millis(50));
-        }
+    /// El lector espera en `READERS` mientras haya un escritor activo o, para que los
+    /// escritores no sufran inanición, mientras haya escritores esperando. Solo se salta esa
+    /// espera si pertenece al grupo de lectores que el último escritor dejó pasar al salir.
+    ///
+    /// # Retorno
+    /// Retorna un `ReadGuard` que termina la lectura al destruirse.
+    pub fn read(&self) -> ReadGuard<'_> {
+        self.monitor.update(|state| {
+            state.waiting_readers += 1;
+            ((), Signal::Nobody)
+        });
+        self.monitor.wait_until(
+            READERS,
+            |state| !state.writer_active && (state.waiting_writers == 0 || state.reader_batch > 0),
+            |state| {
+                state.waiting_readers -= 1;
+                state.reader_batch = state.reader_batch.saturating_sub(1);
+                state.reader_count += 1;
+                ((), Signal::Nobody)
+            },
+        );
+        ReadGuard { monitor: self }
     }
 
     /// Permite que un lector termine la lectura.
     ///
-    /// Este método decrementa el contador de lectores activos (`reader_count`). No notifica directamente
-    /// a otros hilos, ya que el control de espera está implementado en los métodos de escritores.
-    pub fn end_read(&self) {
-        let mut reader_count = self.reader_count.lock().unwrap();
-        *reader_count -= 1;
+    /// Decrementa el contador de lectores y, si era el último, despierta a un escritor.
+    fn end_read(&self) {
+        self.monitor.update(|state| {
+            state.reader_count -= 1;
+            if state.reader_count == 0 && state.reader_batch == 0 {
+                ((), Signal::One(WRITERS))
+            } else {
+                ((), Signal::Nobody)
+            }
+        });
     }
 
     /// Permite que un escritor inicie la escritura.
     ///
-    /// Este método asegura que un escritor solo acceda al recurso compartido si:
-    /// - No hay otros escritores activos (`writer_active` es `false`).
-    /// - No hay lectores activos (`reader_count` es `0`).
+    /// El escritor espera en `WRITERS` mientras haya otro escritor activo, lectores activos
+    /// o lectores del grupo admitido que todavía no han entrado.
     ///
-    /// Si cualquiera de estas condiciones no se cumple, el escritor espera y reintenta después de un breve tiempo.
+    /// # Retorno
+    /// Retorna un `WriteGuard` que termina la escritura al destruirse.
+    pub fn write(&self) -> WriteGuard<'_> {
+        self.monitor.update(|state| {
+            state.waiting_writers += 1;
+            ((), Signal::Nobody)
+        });
+        self.monitor.wait_until(
+            WRITERS,
+            |state| !state.writer_active && state.reader_count == 0 && state.reader_batch == 0,
+            |state| {
+                state.waiting_writers -= 1;
+                state.writer_active = true;
+                ((), Signal::Nobody)
+            },
+        );
+        WriteGuard { monitor: self }
+    }
+
+    /// Permite que un escritor termine la escritura.
     ///
-    /// - Marca el inicio de la escritura configurando `writer_active` en `true`.
-    /// - Utiliza espera activa (`thread::sleep`) para manejar la concurrencia.
-    pub fn start_write(&self) {
-        loop {
-            let mut writer_active = self.writer_active.lock().unwrap();
-            let reader_count = self.reader_count.lock().unwrap();
+    /// Si hay lectores esperando, los deja pasar a todos como un grupo antes que al siguiente
+    /// escritor, así los lectores tampoco sufren inanición. Si no, despierta a un escritor.
+    fn end_write(&self) {
+        self.monitor.update(|state| {
+            state.writer_active = false;
+            if state.waiting_readers > 0 {
+                state.reader_batch = state.waiting_readers;
+                ((), Signal::All(READERS))
+            } else {
+                ((), Signal::One(WRITERS))
+            }
+        });
+    }
+}
+
+impl Drop for ReadGuard<'_> {
+    fn drop(&mut self) {
+        self.monitor.end_read();
+    }
+}
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        self.monitor.end_write();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    /// Espera a que el estado del monitor cumpla `condition`, por ejemplo que un hilo ya se
+    /// haya anotado como esperando.
+    fn wait_for(monitor: &ReaderWriterMonitor, condition: impl Fn(&RwState) -> bool) {
+        while !monitor.monitor.with(|state| condition(state)) {
+            thread::yield_now();
+        }
+    }
 
-            if !*writer_active && *reader_count == 0 {
-                // Si no hay escritores ni lectores activos, marcar que un escritor está escribiendo
-                *writer_active = true;
-                return;
+    #[test]
+    fn wait_until_runs_the_action_once_the_condition_holds() {
+        let monitor = Arc::new(Monitor::new(0, 1));
+        let waiter = thread::spawn({
+            let monitor = Arc::clone(&monitor);
+            move || {
+                monitor.wait_until(0, |count| *count >= 3, |count| {
+                    *count *= 10;
+                    (*count, Signal::Nobody)
+                })
             }
-            // Esperar brevemente antes de reintentar
-            drop(writer_active);
-            drop(reader_count);
-            thread::sleep(Duration::from_millis(50));
+        });
+        for _ in 0..3 {
+            monitor.update(|count| {
+                *count += 1;
+                ((), Signal::All(0))
+            });
         }
+        assert_eq!(waiter.join().unwrap(), 30);
+        assert_eq!(monitor.with(|count| *count), 30);
     }
 
-    /// Permite que un escritor termine la escritura.
-    ///
-    /// Este método libera el acceso exclusivo del escritor al recurso compartido:
-    /// - Configura `writer_active` en `false` para permitir el acceso a lectores o escritores.
-    pub fn end_write(&self) {
-        let mut writer_active = self.writer_active.lock().unwrap();
-        *writer_active = false;
+    #[test]
+    fn a_signal_to_a_queue_wakes_every_waiter_in_it() {
+        let monitor = Arc::new(Monitor::new(false, 2));
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let monitor = Arc::clone(&monitor);
+                thread::spawn(move || monitor.wait_until(1, |open| *open, |_| ((), Signal::Nobody)))
+            })
+            .collect();
+        monitor.update(|open| {
+            *open = true;
+            ((), Signal::All(1))
+        });
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn wait_until_timeout_gives_up_without_running_the_action() {
+        let monitor = Monitor::new(0, 1);
+        let result = monitor.wait_until_timeout(0, Duration::from_millis(20), |count| *count > 0, |count| {
+            *count += 1;
+            (*count, Signal::Nobody)
+        });
+        assert_eq!(result, None);
+        assert_eq!(monitor.with(|count| *count), 0);
+    }
+
+    #[test]
+    fn wait_until_timeout_returns_when_signalled_in_time() {
+        let monitor = Arc::new(Monitor::new(0, 1));
+        let waiter = thread::spawn({
+            let monitor = Arc::clone(&monitor);
+            move || monitor.wait_until_timeout(0, Duration::from_secs(60), |count| *count > 0, |count| (*count, Signal::Nobody))
+        });
+        monitor.update(|count| {
+            *count = 7;
+            ((), Signal::One(0))
+        });
+        assert_eq!(waiter.join().unwrap(), Some(7));
+    }
+
+    #[test]
+    fn end_read_lets_a_waiting_writer_in() {
+        let monitor = Arc::new(ReaderWriterMonitor::new());
+        let reading = monitor.read();
+
+        let writer = thread::spawn({
+            let monitor = Arc::clone(&monitor);
+            move || drop(monitor.write())
+        });
+        wait_for(&monitor, |state| state.waiting_writers == 1);
+        assert!(!writer.is_finished());
+
+        drop(reading);
+        writer.join().unwrap();
+        wait_for(&monitor, |state| !state.writer_active && state.reader_count == 0);
+    }
+
+    #[test]
+    fn end_write_lets_the_waiting_readers_in_together() {
+        let monitor = Arc::new(ReaderWriterMonitor::new());
+        let writing = monitor.write();
+
+        // Cada lector espera dentro de la lectura a los demás: solo terminan si entran juntos.
+        let inside = Arc::new(Barrier::new(3));
+        let readers: Vec<_> = (0..3)
+            .map(|_| {
+                let monitor = Arc::clone(&monitor);
+                let inside = Arc::clone(&inside);
+                thread::spawn(move || {
+                    let _reading = monitor.read();
+                    inside.wait();
+                })
+            })
+            .collect();
+        wait_for(&monitor, |state| state.waiting_readers == 3);
+
+        drop(writing);
+        for reader in readers {
+            reader.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn a_waiting_writer_goes_before_readers_that_arrive_later() {
+        let monitor = Arc::new(ReaderWriterMonitor::new());
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let first_reader = monitor.read();
+
+        let writer = thread::spawn({
+            let monitor = Arc::clone(&monitor);
+            let order = Arc::clone(&order);
+            move || {
+                let _writing = monitor.write();
+                order.lock().unwrap().push("escritor");
+            }
+        });
+        wait_for(&monitor, |state| state.waiting_writers == 1);
+
+        // Un lector que llega con el escritor esperando no entra aunque solo haya lectores dentro.
+        let late_reader = thread::spawn({
+            let monitor = Arc::clone(&monitor);
+            let order = Arc::clone(&order);
+            move || {
+                let _reading = monitor.read();
+                order.lock().unwrap().push("lector");
+            }
+        });
+        wait_for(&monitor, |state| state.waiting_readers == 1);
+        assert!(order.lock().unwrap().is_empty());
+
+        drop(first_reader);
+        writer.join().unwrap();
+        late_reader.join().unwrap();
+        assert_eq!(*order.lock().unwrap(), ["escritor", "lector"]);
+    }
+
+    #[test]
+    fn a_writer_that_panics_does_not_lock_out_readers() {
+        let monitor = Arc::new(ReaderWriterMonitor::new());
+        let writer = thread::spawn({
+            let monitor = Arc::clone(&monitor);
+            move || {
+                let _writing = monitor.write();
+                panic!("el escritor falla con la escritura tomada");
+            }
+        });
+        assert!(writer.join().is_err());
+
+        let reader = thread::spawn({
+            let monitor = Arc::clone(&monitor);
+            move || drop(monitor.read())
+        });
+        reader.join().unwrap();
+        drop(monitor.write());
     }
 }
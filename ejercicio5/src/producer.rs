@@ -1,10 +1,15 @@
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
-use crate::buffer::Buffer;
+use crate::audit::{Audit, Item};
+use crate::buffer::{BoundedBuffer, PutError};
 
 pub struct Producer {
-    buffer: Arc<Mutex<Buffer>>,
+    id: i32,
+    buffer: Arc<BoundedBuffer<Item>>,
+    audit: Arc<Audit>,
+    active_producers: Arc<AtomicI32>,
     items_to_produce: i32,
 }
 
@@ -12,41 +17,72 @@ impl Producer {
     /// Crea una nueva instancia de la clase `Producer`.
     /// 
     /// # Parámetros
-    /// - `buffer`: un `Arc<Mutex<Buffer>>` que apunta al buffer compartido.
+    /// - `id`: identificador del productor.
+    /// - `buffer`: un `Arc<BoundedBuffer<Item>>` que apunta al buffer compartido.
+    /// - `audit`: registro compartido donde se anota cada elemento producido o rechazado.
+    /// - `active_producers`: productores que siguen trabajando; el último en terminar cierra el buffer.
     /// - `items_to_produce`: número de elementos que el productor generará.
     /// 
     /// # Retorno
     /// Retorna una nueva instancia de `Producer` asociada al buffer compartido.
-    pub fn new(buffer: Arc<Mutex<Buffer>>, items_to_produce: i32) -> Self {
-        Producer { buffer, items_to_produce }
+    pub fn new(
+        id: i32,
+        buffer: Arc<BoundedBuffer<Item>>,
+        audit: Arc<Audit>,
+        active_producers: Arc<AtomicI32>,
+        items_to_produce: i32,
+    ) -> Self {
+        Producer { id, buffer, audit, active_producers, items_to_produce }
     }
 
     /// Ejecuta el productor en un hilo separado.
     /// 
     /// @Arc::clone() se debe clonar para no mover toda la propiedad lo que lo haria inaccesible
-    /// @lock().unwrap() espera hasta que el buffer tenga espacio disponible y continua 
+    /// @put_timeout() espera hasta un segundo a que el buffer tenga espacio; si no lo hay avisa y sigue esperando con put()
+    /// @close() el último productor en terminar cierra el buffer para que los consumidores no esperen para siempre
     /// @sleep() simula tiempo
     /// 
     /// # Retorno
     /// Retorna un `JoinHandle<()>` que representa el hilo en el cual el productor está ejecutándose.
     pub fn run(&self) -> thread::JoinHandle<()> {
         let buffer_clone = Arc::clone(&self.buffer);
+        let audit = Arc::clone(&self.audit);
+        let active_producers = Arc::clone(&self.active_producers);
+        let id = self.id;
         let items = self.items_to_produce;
         thread::spawn(move || {
-            for i in 1..=items {
-                loop {
-                    {
-                        let mut buf = buffer_clone.lock().unwrap();
-                        if !buf.is_full() {
-                            buf.data.push(i);
-                            println!("Produjo: {}", i);
-                            break;
+            for sequence in 1..=items {
+                let item = Item { producer: id, sequence };
+                let stored = match buffer_clone.put_timeout(item, Duration::from_secs(1)) {
+                    Ok(()) => Ok(()),
+                    Err(PutError::Timeout(item)) => {
+                        println!("Productor {} espera espacio en el buffer...", id);
+                        buffer_clone.put(item)
+                    }
+                    Err(PutError::Closed(item)) => Err(item),
+                };
+                match stored {
+                    Ok(()) => {
+                        audit.produced(item);
+                        println!("Productor {} produjo: {}", id, item);
+                    }
+                    Err(item) => {
+                        println!("Productor {} no pudo agregar {}: el buffer está cerrado.", id, item);
+                        audit.rejected(item);
+                        // Los elementos que faltan tampoco entrarán.
+                        for sequence in item.sequence + 1..=items {
+                            audit.rejected(Item { producer: id, sequence });
                         }
+                        break;
                     }
-                    thread::sleep(Duration::from_millis(50));
                 }
                 thread::sleep(Duration::from_millis(100));
             }
+
+            if active_producers.fetch_sub(1, Ordering::SeqCst) == 1 {
+                println!("Productor {} fue el último en terminar: cierra el buffer.", id);
+                buffer_clone.close();
+            }
         })
     }
 }
@@ -0,0 +1,108 @@
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+use crate::channel::{Channel, ThreadChannel, TokioChannel};
+
+/// Forma de ejecutar las tareas de un escenario y canal que les corresponde.
+///
+/// El código de productores y consumidores se escribe una sola vez como tareas asíncronas.
+/// Con `ThreadRuntime` cada tarea corre en su propio hilo del sistema y todas sus esperas
+/// bloquean ese hilo; con `TokioRuntime` las tareas comparten hilos y sus esperas ceden el
+/// control. Cada runtime crea su propio tipo de canal, así no se mezclan los dos modelos.
+pub trait Runtime {
+    /// Nombre del backend en los reportes.
+    const NAME: &'static str;
+
+    /// Canal que usan las tareas de este runtime.
+    type Channel<T: Send + 'static>: Channel<T>;
+
+    /// Tarea lanzada con `spawn`.
+    type Handle: Send;
+
+    /// Crea un canal vacío y abierto.
+    ///
+    /// # Parámetros
+    /// - `capacity`: número máximo de mensajes en el canal.
+    fn channel<T: Send + 'static>(capacity: usize) -> Self::Channel<T>;
+
+    /// Simula trabajo durante `duration`.
+    fn pause(duration: Duration) -> impl Future<Output = ()> + Send;
+
+    /// Lanza una tarea.
+    fn spawn(task: impl Future<Output = ()> + Send + 'static) -> Self::Handle;
+
+    /// Espera a que una tarea termine.
+    fn join(handle: Self::Handle) -> impl Future<Output = ()> + Send;
+
+    /// Ejecuta una tarea hasta el final desde código síncrono.
+    fn block_on<R>(task: impl Future<Output = R>) -> R;
+}
+
+/// Backend de hilos del sistema: un hilo por tarea y esperas bloqueantes.
+pub struct ThreadRuntime;
+
+impl Runtime for ThreadRuntime {
+    const NAME: &'static str = "hilos";
+
+    type Channel<T: Send + 'static> = ThreadChannel<T>;
+
+    type Handle = thread::JoinHandle<()>;
+
+    fn channel<T: Send + 'static>(capacity: usize) -> Self::Channel<T> {
+        ThreadChannel::new(capacity)
+    }
+
+    async fn pause(duration: Duration) {
+        thread::sleep(duration)
+    }
+
+    fn spawn(task: impl Future<Output = ()> + Send + 'static) -> Self::Handle {
+        thread::spawn(move || Self::block_on(task))
+    }
+
+    async fn join(handle: Self::Handle) {
+        handle.join().unwrap()
+    }
+
+    /// Ejecuta la tarea en el hilo actual. Todas las esperas de este runtime bloquean el hilo
+    /// en lugar de suspender la tarea, así que la tarea termina en la primera consulta.
+    fn block_on<R>(task: impl Future<Output = R>) -> R {
+        match pin!(task).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(result) => result,
+            Poll::Pending => panic!("una tarea de ThreadRuntime se suspendió: solo puede esperar bloqueando su hilo"),
+        }
+    }
+}
+
+/// Backend de tokio: tareas asíncronas sobre los hilos del runtime y esperas que ceden el control.
+pub struct TokioRuntime;
+
+impl Runtime for TokioRuntime {
+    const NAME: &'static str = "tokio";
+
+    type Channel<T: Send + 'static> = TokioChannel<T>;
+
+    type Handle = tokio::task::JoinHandle<()>;
+
+    fn channel<T: Send + 'static>(capacity: usize) -> Self::Channel<T> {
+        TokioChannel::new(capacity)
+    }
+
+    fn pause(duration: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(duration)
+    }
+
+    fn spawn(task: impl Future<Output = ()> + Send + 'static) -> Self::Handle {
+        tokio::spawn(task)
+    }
+
+    async fn join(handle: Self::Handle) {
+        handle.await.unwrap()
+    }
+
+    fn block_on<R>(task: impl Future<Output = R>) -> R {
+        tokio::runtime::Runtime::new().unwrap().block_on(task)
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::channel::Channel;
+use crate::runtime::{Runtime, ThreadRuntime, TokioRuntime};
+
+/// Escenario de productores y consumidores que se ejecuta igual en cualquier backend.
+pub struct Workload {
+    pub name: &'static str,           // Nombre del escenario en el reporte
+    pub producers: usize,             // Número de productores
+    pub consumers: usize,             // Número de consumidores
+    pub messages_per_producer: usize, // Mensajes que envía cada productor
+    pub capacity: usize,              // Capacidad del canal
+    pub produce_time: Duration,       // Trabajo simulado antes de enviar cada mensaje
+    pub consume_time: Duration,       // Trabajo simulado después de recibir cada mensaje
+}
+
+/// Escenarios que se comparan en el menú.
+pub const WORKLOADS: [Workload; 3] = [
+    Workload {
+        name: "ráfaga sin trabajo",
+        producers: 4,
+        consumers: 4,
+        messages_per_producer: 5000,
+        capacity: 64,
+        produce_time: Duration::ZERO,
+        consume_time: Duration::ZERO,
+    },
+    Workload {
+        name: "consumidores lentos",
+        producers: 2,
+        consumers: 8,
+        messages_per_producer: 200,
+        capacity: 16,
+        produce_time: Duration::ZERO,
+        consume_time: Duration::from_millis(2),
+    },
+    Workload {
+        name: "muchos participantes",
+        producers: 200,
+        consumers: 200,
+        messages_per_producer: 20,
+        capacity: 32,
+        produce_time: Duration::from_millis(1),
+        consume_time: Duration::from_millis(1),
+    },
+];
+
+/// Resultado de ejecutar un escenario en un backend.
+pub struct WorkloadReport {
+    pub messages: usize,          // Mensajes recibidos
+    pub elapsed: Duration,        // Tiempo total, desde el primer envío hasta el último consumidor
+    pub mean_latency: Duration,   // Latencia media entre envío y recepción
+    pub p50_latency: Duration,    // Mediana de la latencia
+    pub p99_latency: Duration,    // Percentil 99 de la latencia
+    pub max_latency: Duration,    // Latencia máxima
+}
+
+impl WorkloadReport {
+    /// Mensajes recibidos por segundo.
+    pub fn throughput(&self) -> f64 {
+        self.messages as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Ejecuta un escenario en el backend `R`. Cada mensaje lleva el instante en que se envió,
+/// así el consumidor mide la latencia al recibirlo. El canal se cierra cuando terminan
+/// todos los productores, y los consumidores terminan al vaciarlo.
+///
+/// # Parámetros
+/// - `workload`: escenario a ejecutar.
+///
+/// # Retorno
+/// Retorna un `WorkloadReport` con el rendimiento y la latencia medidos.
+pub fn run_workload<R: Runtime>(workload: &Workload) -> WorkloadReport {
+    let messages_per_producer = workload.messages_per_producer;
+    let produce_time = workload.produce_time;
+    let consume_time = workload.consume_time;
+
+    R::block_on(async move {
+        let channel = R::channel::<Instant>(workload.capacity);
+        let latencies = Arc::new(Mutex::new(Vec::new()));
+        let start = Instant::now();
+
+        let producers: Vec<_> = (0..workload.producers)
+            .map(|_| {
+                let channel = channel.clone();
+                R::spawn(async move {
+                    for _ in 0..messages_per_producer {
+                        if !produce_time.is_zero() {
+                            R::pause(produce_time).await;
+                        }
+                        if channel.send(Instant::now()).await.is_err() {
+                            return;
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..workload.consumers)
+            .map(|_| {
+                let channel = channel.clone();
+                let latencies = Arc::clone(&latencies);
+                R::spawn(async move {
+                    let mut measured = Vec::new();
+                    while let Some(sent) = channel.recv().await {
+                        measured.push(sent.elapsed());
+                        if !consume_time.is_zero() {
+                            R::pause(consume_time).await;
+                        }
+                    }
+                    latencies.lock().unwrap().extend(measured);
+                })
+            })
+            .collect();
+
+        for producer in producers {
+            R::join(producer).await;
+        }
+        channel.close();
+        for consumer in consumers {
+            R::join(consumer).await;
+        }
+        let elapsed = start.elapsed();
+
+        let mut latencies = latencies.lock().unwrap().clone();
+        latencies.sort();
+        let percentile = |p: usize| {
+            latencies
+                .get((latencies.len() * p / 100).min(latencies.len().saturating_sub(1)))
+                .copied()
+                .unwrap_or_default()
+        };
+        let total: Duration = latencies.iter().sum();
+
+        WorkloadReport {
+            messages: latencies.len(),
+            elapsed,
+            mean_latency: total / latencies.len().max(1) as u32,
+            p50_latency: percentile(50),
+            p99_latency: percentile(99),
+            max_latency: latencies.last().copied().unwrap_or_default(),
+        }
+    })
+}
+
+/// Ejecuta cada escenario en los dos backends e imprime una tabla comparativa.
+pub fn compare_backends() {
+    for workload in &WORKLOADS {
+        println!(
+            "===== {}: {} productores, {} consumidores, {} mensajes, capacidad {} =====",
+            workload.name,
+            workload.producers,
+            workload.consumers,
+            workload.producers * workload.messages_per_producer,
+            workload.capacity
+        );
+        println!(
+            "{:<8} {:>10} {:>12} {:>11} {:>11} {:>11} {:>11}",
+            "Backend", "Tiempo", "Mensajes/s", "Lat. media", "Lat. p50", "Lat. p99", "Lat. máx"
+        );
+
+        let reports = [
+            (ThreadRuntime::NAME, run_workload::<ThreadRuntime>(workload)),
+            (TokioRuntime::NAME, run_workload::<TokioRuntime>(workload)),
+        ];
+        for (name, report) in reports {
+            println!(
+                "{:<8} {:>9.3}s {:>12.0} {:>9.3}ms {:>9.3}ms {:>9.3}ms {:>9.3}ms",
+                name,
+                report.elapsed.as_secs_f64(),
+                report.throughput(),
+                report.mean_latency.as_secs_f64() * 1000.0,
+                report.p50_latency.as_secs_f64() * 1000.0,
+                report.p99_latency.as_secs_f64() * 1000.0,
+                report.max_latency.as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: Workload = Workload {
+        name: "prueba",
+        producers: 3,
+        consumers: 2,
+        messages_per_producer: 50,
+        capacity: 4,
+        produce_time: Duration::ZERO,
+        consume_time: Duration::from_micros(100),
+    };
+
+    #[test]
+    fn both_backends_deliver_every_message_of_the_same_workload() {
+        for report in [run_workload::<ThreadRuntime>(&SMALL), run_workload::<TokioRuntime>(&SMALL)] {
+            assert_eq!(report.messages, SMALL.producers * SMALL.messages_per_producer);
+            assert!(report.p50_latency <= report.p99_latency && report.p99_latency <= report.max_latency);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "se suspendió")]
+    fn a_thread_runtime_task_cannot_suspend() {
+        ThreadRuntime::block_on(tokio::task::yield_now());
+    }
+}
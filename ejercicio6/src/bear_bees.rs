@@ -1,60 +1,428 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use tokio::sync::{Mutex, Semaphore};
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
+use crate::clock::SimClock;
+use crate::colony::Colony;
 
-/// Representa el tarro de miel compartido.
+/// Contadores de un tarro, para el reporte de la colonia.
+#[derive(Clone, Copy)]
+pub struct JarStats {
+    pub last_change: Duration,  // Última vez que cambió el nivel del tarro, en tiempo simulado
+    pub level_area: f64,        // Integral del nivel en el tiempo, en porciones-segundo
+    pub portions: usize,        // Porciones añadidas por las abejas
+    pub bear_wakes: usize,      // Veces que el oso se despertó a comer
+    pub blocked_waits: usize,   // Veces que una abeja encontró el tarro sin espacio
+    pub blocked_time: Duration, // Tiempo total que las abejas esperaron espacio
+    pub emptied_at: Duration,   // Última vez que el tarro quedó vacío, en tiempo simulado
+    pub fills: usize,           // Veces que el tarro se llenó
+    pub fill_time: Duration,    // Tiempo total desde que quedó vacío hasta que se llenó
+}
+
+impl JarStats {
+    /// Acumula el nivel del tarro desde el último cambio hasta `now`. Se llama justo antes
+    /// de cambiarlo.
+    fn record_level(&mut self, level: usize, now: Duration) {
+        self.level_area += level as f64 * (now - self.last_change).as_secs_f64();
+        self.last_change = now;
+    }
+}
+
+/// Evento de un tarro. Cada tarro avisa sus eventos a un observador: la colonia los imprime
+/// y las pruebas los registran para comprobar el protocolo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JarEvent {
+    Added { bee: usize, level: usize, permits: usize }, // Una abeja añadió miel; nivel y permisos libres después
+    BearWoke,                                           // El oso se despertó y empieza a comer
+    Emptied { permits: usize },                         // El oso vació el tarro; permisos libres después
+}
+
+/// Observador de los eventos de un tarro. Puede llamarse con el nivel del tarro bloqueado,
+/// así que no debe esperar a nada del tarro.
+pub type JarObserver = Box<dyn Fn(&HoneyJar, JarEvent) + Send + Sync>;
+
+/// Observador de la colonia: imprime cada evento del tarro.
+pub fn print_event(jar: &HoneyJar, event: JarEvent) {
+    match event {
+        JarEvent::Added { bee, level, .. } => {
+            println!(
+                "Abeja {} añadió una porción de miel al tarro {}. Miel actual: {}/{}",
+                bee, jar.id, level, jar.capacity
+            );
+            if level == jar.capacity {
+                println!("Abeja {} llenó el tarro {}. Despierta a su oso.", bee, jar.id);
+            }
+        }
+        JarEvent::BearWoke => {
+            println!("El oso del tarro {} se despierta y empieza a comer. Las abejas esperan.", jar.id)
+        }
+        JarEvent::Emptied { .. } => println!("El oso del tarro {} se comió toda la miel y vuelve a dormir.", jar.id),
+    }
+}
+
+/// Representa un tarro de miel con su propio oso.
+///
+/// El protocolo entre las abejas y el oso se expresa solo con los dos semáforos:
+/// - `bees_allowed` tiene un permiso por cada porción libre del tarro. Cada abeja consume
+///   un permiso antes de añadir miel, así que con el tarro lleno (y mientras el oso come)
+///   todas las abejas quedan bloqueadas.
+/// - `bear_active` empieza sin permisos y el oso duerme esperándolo. La abeja que llena el
+///   tarro añade un permiso para despertarlo.
+///
+/// Cuando el oso termina de comer vacía el tarro y devuelve `capacity` permisos a
+/// `bees_allowed`, lo que despierta a las abejas.
 pub struct HoneyJar {
+    id: usize,
     capacity: usize,
+    eating_time: Duration,
     current: Mutex<usize>,
-    bees_allowed: Semaphore, // Controla si las abejas pueden producir.
-    bear_active: Semaphore,  // Controla si el oso está activo (pausa la producción de abejas).
+    bees_allowed: Semaphore, // Un permiso por porción libre: las abejas solo producen si hay espacio.
+    bear_active: Semaphore,  // La abeja que llena el tarro despierta al oso con un permiso.
+    stats: StdMutex<JarStats>,
+    clock: Arc<SimClock>,    // Reloj simulado de la colonia
+    observer: JarObserver,   // Recibe cada evento del tarro
 }
 
 impl HoneyJar {
-    pub fn new(capacity: usize) -> Arc<Self> {
+    pub fn new(id: usize, capacity: usize, eating_time: Duration, clock: Arc<SimClock>, observer: JarObserver) -> Arc<Self> {
+        let now = clock.now();
         Arc::new(Self {
+            id,
             capacity,
+            eating_time,
             current: Mutex::new(0),
-            bees_allowed: Semaphore::new(capacity), // Limita a las abejas para evitar sobrellenar el tarro.
-            bear_active: Semaphore::new(1), // Las abejas trabajan mientras el oso no está activo.
+            bees_allowed: Semaphore::new(capacity), // El tarro empieza vacío: toda la capacidad está libre.
+            bear_active: Semaphore::new(0), // El oso empieza dormido.
+            stats: StdMutex::new(JarStats {
+                last_change: now,
+                level_area: 0.0,
+                portions: 0,
+                bear_wakes: 0,
+                blocked_waits: 0,
+                blocked_time: Duration::ZERO,
+                emptied_at: now,
+                fills: 0,
+                fill_time: Duration::ZERO,
+            }),
+            clock,
+            observer,
         })
     }
 
-    /// Incrementa la cantidad de miel en el tarro y llama al oso si está lleno.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Fracción del tarro ocupada o reservada por abejas. Mientras el oso come vale 1.
+    pub fn fullness(&self) -> f64 {
+        1.0 - self.bees_allowed.available_permits() as f64 / self.capacity as f64
+    }
+
+    /// Retorna los contadores del tarro, con el nivel acumulado hasta ahora.
+    pub async fn stats(&self) -> JarStats {
+        let level = *self.current.lock().await;
+        let mut stats = self.stats.lock().unwrap();
+        stats.record_level(level, self.clock.now());
+        *stats
+    }
+
+    /// Añade una porción de miel al tarro y despierta al oso si lo llena.
     pub async fn add_honey(&self, bee_id: usize) {
-        // Esperar a que el oso no esté activo.
-        let _bear_inactive = self.bear_active.acquire().await.unwrap();
-        // Esperar a que haya espacio en el tarro.
-        let _permit = self.bees_allowed.acquire().await.unwrap();
+        // Esperar a que haya espacio en el tarro. No hay espacio mientras el oso come.
+        match self.bees_allowed.try_acquire() {
+            Ok(permit) => permit.forget(),
+            Err(_) => {
+                let started = self.clock.now();
+                self.bees_allowed.acquire().await.unwrap().forget();
+                let mut stats = self.stats.lock().unwrap();
+                stats.blocked_waits += 1;
+                stats.blocked_time += self.clock.now() - started;
+            }
+        }
 
         let mut honey = self.current.lock().await;
+        {
+            let mut stats = self.stats.lock().unwrap();
+            stats.record_level(*honey, self.clock.now());
+            stats.portions += 1;
+        }
         *honey += 1;
-        println!(
-            "Abeja {} añadió una porción de miel. Miel actual: {}/{}",
-            bee_id, *honey, self.capacity
-        );
+        (self.observer)(self, JarEvent::Added {
+            bee: bee_id,
+            level: *honey,
+            permits: self.bees_allowed.available_permits(),
+        });
 
-        // Si el tarro está lleno, el oso consume la miel inmediatamente.
-        if *honey >= self.capacity {
-            println!(
-                "Abeja {} llenó el tarro. Llamando al oso para consumir la miel...",
-                bee_id
-            );
-            *honey = 0; // Vacía el tarro.
-            println!("El oso consume la miel y vuelve a dormir. El tarro está vacío.");
-
-            // Permitir que las abejas vuelvan a llenar el tarro.
-            self.bees_allowed.add_permits(self.capacity);
+        // Si el tarro está lleno, despertar al oso.
+        if *honey == self.capacity {
+            let mut stats = self.stats.lock().unwrap();
+            stats.fills += 1;
+            let fill_time = self.clock.now() - stats.emptied_at;
+            stats.fill_time += fill_time;
+            drop(stats);
+            self.bear_active.add_permits(1);
         }
+    }
 
-        self.bear_active.add_permits(1); // Permitir que las abejas continúen.
+    /// Espera a que el tarro se llene, come durante `eating_time` y lo vacía.
+    pub async fn feed_bear(&self) {
+        // Dormir hasta que la abeja que llena el tarro lo despierte.
+        self.bear_active.acquire().await.unwrap().forget();
+        self.stats.lock().unwrap().bear_wakes += 1;
+        (self.observer)(self, JarEvent::BearWoke);
+        self.clock.sleep(self.eating_time).await;
+
+        let mut honey = self.current.lock().await;
+        let now = self.clock.now();
+        let mut stats = self.stats.lock().unwrap();
+        stats.record_level(*honey, now);
+        stats.emptied_at = now;
+        drop(stats);
+        *honey = 0; // Vacía el tarro.
+
+        // Permitir que las abejas vuelvan a llenar el tarro.
+        self.bees_allowed.add_permits(self.capacity);
+        (self.observer)(self, JarEvent::Emptied {
+            permits: self.bees_allowed.available_permits(),
+        });
     }
 }
 
-/// Representa una abeja productora.
-pub async fn bee_task(honey_jar: Arc<HoneyJar>, bee_id: usize) {
+/// Tiempo mínimo que tarda una abeja en producir una porción.
+pub const MIN_PRODUCTION_TIME: Duration = Duration::from_millis(300);
+/// Tiempo máximo que tarda una abeja en producir una porción.
+pub const MAX_PRODUCTION_TIME: Duration = Duration::from_millis(700);
+
+/// Representa una abeja productora, que lleva cada porción al tarro que elige la colonia.
+/// Sus tiempos de producción salen de un generador propio, sembrado con `seed` y su id,
+/// así que no dependen del orden en que se ejecutan las demás abejas.
+pub async fn bee_task(colony: Arc<Colony>, bee_id: usize, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(bee_id as u64));
     loop {
-        honey_jar.add_honey(bee_id).await;
-        sleep(Duration::from_millis(500)).await; // Simula el tiempo de producción.
+        colony.choose_jar(bee_id).add_honey(bee_id).await;
+        colony.portion_added(bee_id);
+        // Simula el tiempo de producción.
+        colony.clock().sleep(rng.gen_range(MIN_PRODUCTION_TIME..=MAX_PRODUCTION_TIME)).await;
+    }
+}
+
+/// Representa al oso de un tarro, que duerme hasta que el tarro se llena.
+pub async fn bear_task(colony: Arc<Colony>, jar: usize) {
+    loop {
+        colony.jars()[jar].feed_bear().await;
+        colony.feeding_done();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::{any, prop_assert, proptest, ProptestConfig, Strategy};
+    use tokio::task;
+    use crate::clock;
+
+    /// Parámetros de una simulación aleatoria de un tarro.
+    #[derive(Debug)]
+    struct Scenario {
+        bees: usize,
+        capacity: usize,
+        eating_time: Duration,
+        min_production: Duration,
+        max_production: Duration,
+        seed: u64,
+    }
+
+    /// La producción dura al menos 1 ms: con producción y comida instantáneas el tarro se
+    /// llenaría y vaciaría para siempre sin que avance el reloj.
+    fn scenarios() -> impl Strategy<Value = Scenario> {
+        (1..8usize, 1..8usize, 0..1500u64, 1..400u64, 0..400u64, any::<u64>()).prop_map(
+            |(bees, capacity, eating_ms, min_ms, spread_ms, seed)| Scenario {
+                bees,
+                capacity,
+                eating_time: Duration::from_millis(eating_ms),
+                min_production: Duration::from_millis(min_ms),
+                max_production: Duration::from_millis(min_ms + spread_ms),
+                seed,
+            },
+        )
+    }
+
+    /// Tiempo simulado de cada caso.
+    const SIMULATED_TIME: Duration = Duration::from_secs(10);
+
+    /// Ejecuta un tarro con su oso y sus abejas durante `SIMULATED_TIME` con el reloj simulado,
+    /// registrando sus eventos con un observador. En cada punto de reposo, con todas las tareas
+    /// esperando y antes de que avance el reloj, compara los permisos de `bees_allowed` con el
+    /// espacio libre del tarro. Como el tiempo avanza solo en esos puntos, cada caso es reproducible.
+    ///
+    /// # Retorno
+    /// Retorna los eventos del tarro y los puntos de reposo en los que no coincidían, como
+    /// `(nivel, permisos)`.
+    fn simulate(scenario: &Scenario) -> (Vec<JarEvent>, Vec<(usize, usize)>) {
+        let events = Arc::new(StdMutex::new(Vec::new()));
+        let recorder: JarObserver = Box::new({
+            let events = Arc::clone(&events);
+            move |_, event| events.lock().unwrap().push(event)
+        });
+        let clock = SimClock::new();
+        let jar = HoneyJar::new(1, scenario.capacity, scenario.eating_time, Arc::clone(&clock), recorder);
+
+        let mismatches = Arc::new(StdMutex::new(Vec::new()));
+        let on_settled = {
+            let jar = Arc::clone(&jar);
+            let mismatches = Arc::clone(&mismatches);
+            move || {
+                if let Err(mismatch) = settled_level_and_permits(&jar) {
+                    mismatches.lock().unwrap().push(mismatch);
+                }
+            }
+        };
+
+        clock::block_on(&clock, on_settled, async {
+            let mut tasks = vec![task::spawn({
+                let jar = jar.clone();
+                async move {
+                    loop {
+                        jar.feed_bear().await;
+                    }
+                }
+            })];
+            for bee_id in 1..=scenario.bees {
+                let jar = jar.clone();
+                let (min, max) = (scenario.min_production, scenario.max_production);
+                let mut rng = StdRng::seed_from_u64(scenario.seed.wrapping_add(bee_id as u64));
+                let clock = Arc::clone(&clock);
+                tasks.push(task::spawn(async move {
+                    loop {
+                        jar.add_honey(bee_id).await;
+                        clock.sleep(rng.gen_range(min..=max)).await;
+                    }
+                }));
+            }
+
+            clock.sleep(SIMULATED_TIME).await;
+            for task in tasks {
+                task.abort();
+            }
+        });
+
+        let events = events.lock().unwrap().clone();
+        let mismatches = mismatches.lock().unwrap().clone();
+        (events, mismatches)
+    }
+
+    /// Compara los permisos libres con el espacio libre del tarro en un punto de reposo.
+    ///
+    /// En reposo no hay permisos en tránsito: una abeja a la que el oso entregó un permiso
+    /// está lista para ejecutarse hasta añadir su miel, así que el runtime no reposa antes.
+    /// Por eso aquí tiene que darse la igualdad exacta.
+    ///
+    /// # Retorno
+    /// Retorna `Err` con `(nivel, permisos)` si los permisos no igualan el espacio libre.
+    fn settled_level_and_permits(jar: &HoneyJar) -> Result<(), (usize, usize)> {
+        // Ninguna tarea retiene el mutex entre dos puntos de espera.
+        let reading = (*jar.current.try_lock().unwrap(), jar.bees_allowed.available_permits());
+        if reading.0 + reading.1 == jar.capacity {
+            Ok(())
+        } else {
+            Err(reading)
+        }
+    }
+
+    /// Comprueba que los eventos de un tarro siguen el protocolo entre las abejas y el oso.
+    ///
+    /// # Retorno
+    /// Retorna el número de veces que se llenó el tarro, o la descripción del primer evento
+    /// que rompe el protocolo.
+    fn check_events(events: &[JarEvent], capacity: usize) -> Result<usize, String> {
+        let mut level = 0;
+        let mut fills = 0;
+        let mut emptyings = 0;
+        let mut eating = false;
+        for (index, event) in events.iter().enumerate() {
+            match *event {
+                JarEvent::Added { level: new_level, permits, .. } => {
+                    if eating {
+                        return Err(format!("evento {}: una abeja añadió miel mientras el oso comía", index));
+                    }
+                    if new_level != level + 1 || new_level > capacity {
+                        return Err(format!("evento {}: el nivel pasó de {} a {} (capacidad {})", index, level, new_level, capacity));
+                    }
+                    // Aquí no se puede exigir la igualdad. Al vaciar el tarro, `add_permits`
+                    // entrega los permisos directamente a las abejas que esperaban, y cada una
+                    // los descuenta antes de añadir su miel. Hasta que todas lo hacen, nivel más
+                    // permisos queda por debajo de la capacidad. `simulate` exige la igualdad en
+                    // cada punto de reposo, cuando ya no quedan permisos en tránsito.
+                    if new_level + permits > capacity {
+                        return Err(format!("evento {}: nivel {} con {} permisos libres", index, new_level, permits));
+                    }
+                    level = new_level;
+                    if level == capacity {
+                        fills += 1;
+                    }
+                }
+                JarEvent::BearWoke => {
+                    if eating || level != capacity {
+                        return Err(format!("evento {}: el oso se despertó con el tarro en {}/{}", index, level, capacity));
+                    }
+                    eating = true;
+                }
+                JarEvent::Emptied { permits } => {
+                    if !eating {
+                        return Err(format!("evento {}: el tarro se vació sin que el oso comiera", index));
+                    }
+                    if permits > capacity {
+                        return Err(format!("evento {}: {} permisos libres con capacidad {}", index, permits, capacity));
+                    }
+                    eating = false;
+                    level = 0;
+                    emptyings += 1;
+                }
+            }
+        }
+        // Un llenado puede quedar sin vaciar si la simulación termina mientras el oso come.
+        if fills != emptyings && fills != emptyings + 1 {
+            return Err(format!("{} llenados y {} vaciados", fills, emptyings));
+        }
+        Ok(fills)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(48))]
+
+        #[test]
+        fn honey_jar_follows_the_protocol(scenario in scenarios()) {
+            let (events, mismatches) = simulate(&scenario);
+            prop_assert!(mismatches.is_empty(), "permisos distintos del espacio libre: {:?}", mismatches);
+            let fills = check_events(&events, scenario.capacity);
+            prop_assert!(fills.is_ok(), "{}", fills.unwrap_err());
+            prop_assert!(fills.unwrap() > 0, "el tarro nunca se llenó");
+        }
+    }
+
+    #[test]
+    fn check_events_rejects_honey_added_while_the_bear_eats() {
+        let events = [
+            JarEvent::Added { bee: 1, level: 1, permits: 0 },
+            JarEvent::BearWoke,
+            JarEvent::Added { bee: 1, level: 2, permits: 0 },
+        ];
+        assert!(check_events(&events, 1).unwrap_err().contains("mientras el oso comía"));
+    }
+
+    #[test]
+    fn check_events_rejects_a_fill_emptied_twice() {
+        let events = [
+            JarEvent::Added { bee: 1, level: 1, permits: 0 },
+            JarEvent::BearWoke,
+            JarEvent::Emptied { permits: 1 },
+            JarEvent::Emptied { permits: 1 },
+        ];
+        assert!(check_events(&events, 1).is_err());
     }
 }
@@ -0,0 +1,145 @@
+/*
+Reloj simulado de la colonia.
+
+Las abejas y los osos esperan con `SimClock::sleep` en lugar de con el reloj de tokio. El
+tiempo simulado no avanza solo: `block_on` ejecuta la simulación en un runtime de un solo
+hilo y, cada vez que el runtime se queda sin tareas listas (justo antes de dormir el hilo),
+adelanta el reloj hasta la próxima espera que vence y despierta a las tareas que esperaban
+ese instante. Así una simulación larga termina enseguida, y como el orden de las tareas
+depende solo de sus tiempos, la misma semilla da siempre el mismo resultado.
+*/
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use tokio::runtime::Builder;
+
+/// Esperas pendientes del reloj, protegidas por su mutex.
+struct ClockState {
+    now: Duration,                                   // Tiempo simulado desde el inicio
+    deadlines: BinaryHeap<Reverse<(Duration, u64)>>, // (vencimiento, número) de cada espera, la más próxima primero
+    wakers: BTreeMap<u64, Waker>,                    // Tarea de cada espera que sigue pendiente
+    next_sleep: u64,                                 // Número de la próxima espera
+}
+
+/// Reloj de tiempo simulado que avanza cuando todas las tareas esperan.
+pub struct SimClock {
+    state: Mutex<ClockState>,
+}
+
+impl SimClock {
+    /// Crea un reloj en el instante 0 sin esperas pendientes.
+    pub fn new() -> Arc<Self> {
+        Arc::new(SimClock {
+            state: Mutex::new(ClockState {
+                now: Duration::ZERO,
+                deadlines: BinaryHeap::new(),
+                wakers: BTreeMap::new(),
+                next_sleep: 0,
+            }),
+        })
+    }
+
+    /// Tiempo simulado transcurrido desde el inicio.
+    pub fn now(&self) -> Duration {
+        self.state.lock().unwrap().now
+    }
+
+    /// Espera `duration` de tiempo simulado.
+    pub fn sleep(self: &Arc<Self>, duration: Duration) -> Sleep {
+        Sleep { clock: Arc::clone(self), deadline: self.now() + duration, id: None }
+    }
+
+    /// Adelanta el reloj hasta la próxima espera pendiente y despierta a todas las que
+    /// vencen en ese instante, en el orden en que empezaron. Sin esperas pendientes no hace nada.
+    fn advance(&self) {
+        let mut state = self.state.lock().unwrap();
+        // Las esperas canceladas quedan en el montículo hasta llegar al frente.
+        while let Some(&Reverse((deadline, id))) = state.deadlines.peek() {
+            if state.wakers.contains_key(&id) {
+                state.now = state.now.max(deadline);
+                break;
+            }
+            state.deadlines.pop();
+        }
+        let now = state.now;
+        let mut due = Vec::new();
+        while let Some(&Reverse((deadline, id))) = state.deadlines.peek() {
+            if deadline > now {
+                break;
+            }
+            state.deadlines.pop();
+            due.extend(state.wakers.remove(&id));
+        }
+        drop(state);
+        due.into_iter().for_each(Waker::wake);
+    }
+}
+
+/// Espera en curso de `SimClock::sleep`.
+pub struct Sleep {
+    clock: Arc<SimClock>,
+    deadline: Duration, // Instante simulado en que termina
+    id: Option<u64>,    // Número de la espera una vez registrada en el reloj
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.clock.state.lock().unwrap();
+        if state.now >= self.deadline {
+            if let Some(id) = self.id {
+                state.wakers.remove(&id);
+            }
+            return Poll::Ready(());
+        }
+        let id = match self.id {
+            Some(id) => id,
+            None => {
+                let id = state.next_sleep;
+                state.next_sleep += 1;
+                state.deadlines.push(Reverse((self.deadline, id)));
+                id
+            }
+        };
+        state.wakers.insert(id, context.waker().clone());
+        drop(state);
+        self.id = Some(id);
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.clock.state.lock().unwrap().wakers.remove(&id);
+        }
+    }
+}
+
+/// Ejecuta `future` en un runtime de un solo hilo que adelanta `clock` cada vez que no
+/// quedan tareas listas.
+///
+/// # Parámetros
+/// - `clock`: reloj de la simulación.
+/// - `on_settled`: se llama en cada punto de reposo, con todas las tareas esperando y antes
+///   de adelantar el reloj.
+/// - `future`: simulación a ejecutar.
+///
+/// # Retorno
+/// Retorna el resultado de `future`.
+pub fn block_on<F: Future>(clock: &Arc<SimClock>, on_settled: impl Fn() + Send + Sync + 'static, future: F) -> F::Output {
+    let parked_clock = Arc::clone(clock);
+    Builder::new_current_thread()
+        .on_thread_park(move || {
+            on_settled();
+            parked_clock.advance();
+        })
+        .build()
+        .unwrap()
+        .block_on(future)
+}
@@ -0,0 +1,318 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::task;
+use tokio::time::Duration;
+use crate::bear_bees::{bear_task, bee_task, print_event, HoneyJar};
+use crate::clock::{self, SimClock};
+
+/// Cómo elige cada abeja el tarro al que lleva su miel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JarPolicy {
+    Nearest,     // El tarro más cercano a la abeja, siempre el mismo.
+    RoundRobin,  // Los tarros por turnos, compartidos por toda la colonia.
+    LeastFull,   // El tarro con más espacio libre en proporción a su capacidad.
+}
+
+impl fmt::Display for JarPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            JarPolicy::Nearest => "cercano",
+            JarPolicy::RoundRobin => "turnos",
+            JarPolicy::LeastFull => "menos-lleno",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for JarPolicy {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            "cercano" => Ok(JarPolicy::Nearest),
+            "turnos" => Ok(JarPolicy::RoundRobin),
+            "menos-lleno" => Ok(JarPolicy::LeastFull),
+            other => Err(format!(
+                "política '{}' no reconocida (use cercano, turnos o menos-lleno)",
+                other
+            )),
+        }
+    }
+}
+
+/// Parámetros de una simulación de la colonia.
+#[derive(Clone, Debug)]
+pub struct ColonyConfig {
+    pub bees: usize,                  // Número de abejas
+    pub capacities: Vec<usize>,       // Capacidad de cada tarro
+    pub eating_time: Duration,        // Tiempo que tarda cada oso en comer
+    pub policy: JarPolicy,            // Cómo eligen las abejas el tarro
+    pub seed: u64,                    // Semilla de los tiempos de producción
+    pub feedings: Option<usize>,      // Termina tras esta cantidad de comidas de oso
+    pub duration: Option<Duration>,   // Termina tras este tiempo simulado
+}
+
+impl Default for ColonyConfig {
+    fn default() -> Self {
+        ColonyConfig {
+            bees: 5,
+            capacities: vec![5],
+            eating_time: Duration::from_millis(1000),
+            policy: JarPolicy::Nearest,
+            seed: 42,
+            feedings: Some(10),
+            duration: None,
+        }
+    }
+}
+
+/// Colonia de abejas que reparte su miel entre varios tarros, cada uno con su oso.
+///
+/// Las abejas y los tarros están repartidos a lo largo de una línea: la abeja `i` de `n`
+/// está en la posición `(i - 0.5) / n` y el tarro `j` de `m` en `(j - 0.5) / m`.
+pub struct Colony {
+    jars: Vec<Arc<HoneyJar>>,
+    policy: JarPolicy,
+    bees: usize,
+    next_jar: AtomicUsize,          // Siguiente tarro en la política por turnos
+    clock: Arc<SimClock>,           // Reloj simulado, que empieza con la colonia
+    bee_portions: Vec<AtomicUsize>, // Porciones aportadas por cada abeja
+    feedings: AtomicUsize,          // Comidas de oso terminadas en toda la colonia
+    feeding_limit: Option<usize>,   // Comidas tras las que termina la simulación
+    finished: Notify,               // Se notifica al llegar a `feeding_limit`
+}
+
+impl Colony {
+    /// Crea una colonia con un tarro por cada capacidad indicada.
+    pub fn new(config: &ColonyConfig, clock: &Arc<SimClock>) -> Arc<Self> {
+        Arc::new(Colony {
+            jars: config
+                .capacities
+                .iter()
+                .enumerate()
+                .map(|(index, &capacity)| {
+                    HoneyJar::new(index + 1, capacity, config.eating_time, Arc::clone(clock), Box::new(print_event))
+                })
+                .collect(),
+            policy: config.policy,
+            bees: config.bees,
+            next_jar: AtomicUsize::new(0),
+            clock: Arc::clone(clock),
+            bee_portions: (0..config.bees).map(|_| AtomicUsize::new(0)).collect(),
+            feedings: AtomicUsize::new(0),
+            feeding_limit: config.feedings,
+            finished: Notify::new(),
+        })
+    }
+
+    pub fn jars(&self) -> &[Arc<HoneyJar>] {
+        &self.jars
+    }
+
+    pub fn clock(&self) -> &Arc<SimClock> {
+        &self.clock
+    }
+
+    /// Registra una porción aportada por una abeja.
+    pub fn portion_added(&self, bee_id: usize) {
+        self.bee_portions[bee_id - 1].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Registra una comida de oso y avisa si con ella se llega al límite de la simulación.
+    pub fn feeding_done(&self) {
+        let feedings = self.feedings.fetch_add(1, Ordering::Relaxed) + 1;
+        if Some(feedings) == self.feeding_limit {
+            self.finished.notify_one();
+        }
+    }
+
+    /// Elige el tarro al que la abeja lleva la siguiente porción.
+    pub fn choose_jar(&self, bee_id: usize) -> &HoneyJar {
+        let index = match self.policy {
+            JarPolicy::Nearest => {
+                let position = (bee_id as f64 - 0.5) / self.bees as f64;
+                ((position * self.jars.len() as f64) as usize).min(self.jars.len() - 1)
+            }
+            JarPolicy::RoundRobin => self.next_jar.fetch_add(1, Ordering::Relaxed) % self.jars.len(),
+            JarPolicy::LeastFull => self
+                .jars
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.fullness().total_cmp(&b.fullness()))
+                .map(|(index, _)| index)
+                .unwrap(),
+        };
+        &self.jars[index]
+    }
+
+    /// Retorna el resumen de la colonia hasta ahora.
+    pub async fn summary(&self) -> ColonySummary {
+        let elapsed = self.clock.now();
+        let mut jars = Vec::with_capacity(self.jars.len());
+        for jar in &self.jars {
+            let stats = jar.stats().await;
+            let mean_level = if elapsed.is_zero() { 0.0 } else { stats.level_area / elapsed.as_secs_f64() };
+            jars.push(JarSummary {
+                id: jar.id(),
+                capacity: jar.capacity(),
+                mean_level,
+                portions: stats.portions,
+                bear_wakes: stats.bear_wakes,
+                blocked_waits: stats.blocked_waits,
+                blocked_time: stats.blocked_time,
+                mean_fill_time: stats.fill_time / stats.fills.max(1) as u32,
+            });
+        }
+        ColonySummary {
+            policy: self.policy,
+            elapsed,
+            feedings: self.feedings.load(Ordering::Relaxed),
+            jars,
+            bee_portions: self.bee_portions.iter().map(|portions| portions.load(Ordering::Relaxed)).collect(),
+        }
+    }
+}
+
+/// Resumen de un tarro al terminar la simulación.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JarSummary {
+    pub id: usize,
+    pub capacity: usize,
+    pub mean_level: f64,          // Nivel medio en el tiempo, en porciones
+    pub portions: usize,          // Porciones añadidas por las abejas
+    pub bear_wakes: usize,        // Veces que el oso se despertó a comer
+    pub blocked_waits: usize,     // Veces que una abeja encontró el tarro sin espacio
+    pub blocked_time: Duration,   // Tiempo total que las abejas esperaron espacio
+    pub mean_fill_time: Duration, // Tiempo medio desde que quedó vacío hasta que se llenó
+}
+
+/// Resumen de una simulación de la colonia. Con la misma configuración, incluida la
+/// semilla, dos simulaciones dan el mismo resumen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColonySummary {
+    pub policy: JarPolicy,
+    pub elapsed: Duration,        // Tiempo simulado
+    pub feedings: usize,          // Comidas de oso terminadas
+    pub jars: Vec<JarSummary>,    // Resumen de cada tarro, en orden
+    pub bee_portions: Vec<usize>, // Porciones aportadas por cada abeja, en orden
+}
+
+impl ColonySummary {
+    /// Imprime, para cada tarro, su nivel medio, las porciones recibidas, las veces que se
+    /// despertó su oso y cuánto esperaron las abejas por espacio, y las porciones de cada abeja.
+    pub fn print(&self) {
+        println!(
+            "===== Colonia ({}, {:.1}s simulados, {} comidas de oso) =====",
+            self.policy,
+            self.elapsed.as_secs_f64(),
+            self.feedings
+        );
+        println!(
+            "{:<6} {:>9} {:>11} {:>9} {:>13} {:>10} {:>14} {:>14}",
+            "Tarro", "Capacidad", "Nivel medio", "Porciones", "Oso despertó", "Bloqueos", "Espera abejas", "Llenado medio"
+        );
+        for jar in &self.jars {
+            println!(
+                "{:<6} {:>9} {:>10.0}% {:>9} {:>13} {:>10} {:>13.2}s {:>13.2}s",
+                jar.id,
+                jar.capacity,
+                100.0 * jar.mean_level / jar.capacity as f64,
+                jar.portions,
+                jar.bear_wakes,
+                jar.blocked_waits,
+                jar.blocked_time.as_secs_f64(),
+                jar.mean_fill_time.as_secs_f64()
+            );
+        }
+
+        println!("{:<6} {:>9}", "Abeja", "Porciones");
+        for (index, portions) in self.bee_portions.iter().enumerate() {
+            println!("{:<6} {:>9}", index + 1, portions);
+        }
+    }
+}
+
+/// Ejecuta una simulación de la colonia hasta llegar al número de comidas de oso o al
+/// tiempo simulado indicados, lo que ocurra primero, e imprime el resumen.
+///
+/// La simulación corre con su propio reloj simulado, que avanza solo cuando todas las
+/// tareas esperan, así que la misma semilla da el mismo resumen.
+pub fn run_colony(config: ColonyConfig) {
+    let seed = config.seed;
+    let summary = run_simulation(config);
+    println!("Simulación terminada (semilla {}).", seed);
+    summary.print();
+}
+
+/// Ejecuta una simulación de la colonia con un reloj simulado nuevo.
+///
+/// # Retorno
+/// Retorna el resumen de la colonia al terminar.
+fn run_simulation(config: ColonyConfig) -> ColonySummary {
+    let clock = SimClock::new();
+    clock::block_on(&clock, || {}, simulate(config, Arc::clone(&clock)))
+}
+
+/// Simulación de la colonia con el reloj `clock`.
+async fn simulate(config: ColonyConfig, clock: Arc<SimClock>) -> ColonySummary {
+    let colony = Colony::new(&config, &clock);
+
+    // Inicia un oso por tarro y las tareas de las abejas.
+    let mut tasks: Vec<_> = (0..colony.jars().len())
+        .map(|jar| task::spawn(bear_task(colony.clone(), jar)))
+        .collect();
+    for bee_id in 1..=config.bees {
+        tasks.push(task::spawn(bee_task(colony.clone(), bee_id, config.seed)));
+    }
+
+    let limit = async {
+        match config.duration {
+            Some(duration) => clock.sleep(duration).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        biased;
+        _ = colony.finished.notified() => {}
+        _ = limit => {}
+    }
+
+    for task in tasks {
+        task.abort();
+    }
+    colony.summary().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64) -> ColonyConfig {
+        ColonyConfig {
+            bees: 6,
+            capacities: vec![3, 5],
+            eating_time: Duration::from_millis(800),
+            policy: JarPolicy::LeastFull,
+            seed,
+            feedings: Some(12),
+            duration: Some(Duration::from_secs(600)),
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_summary() {
+        let first = run_simulation(config(7));
+        let second = run_simulation(config(7));
+        assert_eq!(first, second);
+        assert_eq!(first.feedings, 12);
+        assert_eq!(first.bee_portions.iter().sum::<usize>(), first.jars.iter().map(|jar| jar.portions).sum::<usize>());
+    }
+
+    #[test]
+    fn another_seed_changes_the_portions_of_each_bee() {
+        assert_ne!(run_simulation(config(7)).bee_portions, run_simulation(config(8)).bee_portions);
+    }
+}
@@ -0,0 +1,340 @@
+/*
+La configuración de la simulación (`ColonyConfig`) se puede dar de tres formas, y las tres
+pasan por `set`, que valida cada valor con un mensaje claro:
+
+- Opciones de la línea de comandos, `--clave valor` o `--clave=valor`. Con cualquier opción
+  la simulación arranca sin preguntar nada.
+- Configuraciones con nombre en un archivo de presets (`presets.ini` del crate por defecto), con una
+  sección `[nombre]` por preset y una línea `clave = valor` por opción. `--preset NOMBRE`
+  carga una; `--presets ARCHIVO` cambia el archivo.
+- Las preguntas del menú, que muestran el valor por defecto entre corchetes y vuelven a
+  preguntar si la respuesta no es válida.
+
+Los valores se aplican en orden, así que una opción después de `--preset` lo modifica.
+*/
+use std::fs;
+use std::io::{self, Write};
+use tokio::time::Duration;
+use crate::colony::ColonyConfig;
+
+/// Archivo de presets que se usa si no se indica otro. Es el `presets.ini` junto al
+/// `Cargo.toml`, así que no depende del directorio desde el que se ejecute el programa.
+pub const DEFAULT_PRESETS_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/presets.ini");
+
+/// Claves de la configuración, en el orden en que se preguntan, con su descripción.
+pub const KEYS: [(&str, &str); 7] = [
+    ("bees", "Número de abejas (1 a 10000)"),
+    ("capacities", "Capacidad de cada tarro, separadas por comas (1 a 1000)"),
+    ("eating-ms", "Tiempo que tarda cada oso en comer, en ms (hasta 60000)"),
+    ("policy", "Cómo eligen las abejas el tarro (cercano, turnos, menos-lleno)"),
+    ("seed", "Semilla de los tiempos de producción"),
+    ("feedings", "Comidas de oso tras las que termina (0 = sin límite)"),
+    ("duration", "Segundos simulados tras los que termina (0 = sin límite)"),
+];
+
+impl ColonyConfig {
+    /// Cambia una opción a partir de su texto.
+    ///
+    /// # Parámetros
+    /// - `key`: nombre de la opción.
+    /// - `value`: nuevo valor, como texto.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match key {
+            "bees" => self.bees = parse_in_range(value, 1, 10_000)?,
+            "capacities" => {
+                self.capacities = value
+                    .split(',')
+                    .map(|capacity| parse_in_range(capacity.trim(), 1, 1000))
+                    .collect::<Result<_, _>>()?;
+            }
+            "eating-ms" => self.eating_time = Duration::from_millis(parse_in_range(value, 0, 60_000)? as u64),
+            "policy" => self.policy = value.parse()?,
+            "seed" => {
+                self.seed = value
+                    .parse()
+                    .map_err(|_| format!("'{}' no es una semilla válida (use un entero no negativo)", value))?
+            }
+            "feedings" => {
+                let feedings = parse_in_range(value, 0, 1_000_000)?;
+                self.feedings = (feedings > 0).then_some(feedings);
+            }
+            "duration" => {
+                let seconds = parse_in_range(value, 0, 1_000_000)?;
+                self.duration = (seconds > 0).then(|| Duration::from_secs(seconds as u64));
+            }
+            _ => return Err(format!("opción '{}' desconocida (use --help para ver la lista)", key)),
+        }
+        Ok(())
+    }
+
+    /// Retorna el valor actual de una opción, escrito como lo acepta `set`.
+    pub fn get(&self, key: &str) -> String {
+        match key {
+            "bees" => self.bees.to_string(),
+            "capacities" => self.capacities.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(","),
+            "eating-ms" => self.eating_time.as_millis().to_string(),
+            "policy" => self.policy.to_string(),
+            "seed" => self.seed.to_string(),
+            "feedings" => self.feedings.unwrap_or(0).to_string(),
+            "duration" => self.duration.map_or(0, |duration| duration.as_secs()).to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Comprueba lo que no se puede validar opción por opción: que la simulación termine.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.feedings.is_none() && self.duration.is_none() {
+            return Err("indique un límite de comidas o de duración; sin ninguno la simulación no termina".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Convierte un texto en un entero dentro de `[min, max]`.
+fn parse_in_range(value: &str, min: usize, max: usize) -> Result<usize, String> {
+    let number: usize = value
+        .parse()
+        .map_err(|_| format!("'{}' no es un número entero válido", value))?;
+    if number < min || number > max {
+        return Err(format!("{} está fuera de rango (debe estar entre {} y {})", number, min, max));
+    }
+    Ok(number)
+}
+
+/// Lee un archivo de presets y aplica el preset indicado sobre `config`.
+///
+/// # Parámetros
+/// - `config`: configuración que se modifica.
+/// - `path`: archivo de presets.
+/// - `name`: nombre del preset.
+pub fn apply_preset(config: &mut ColonyConfig, path: &str, name: &str) -> Result<(), String> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("no se pudo leer el archivo de presets '{}': {}", path, error))?;
+
+    let mut section: Option<&str> = None;
+    let mut found = false;
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            section = Some(header.trim());
+            found |= header.trim() == name;
+            continue;
+        }
+        if section != Some(name) {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("{}:{}: se esperaba 'clave = valor'", path, number + 1))?;
+        config
+            .set(key.trim(), value)
+            .map_err(|error| format!("{}:{}: {}: {}", path, number + 1, key.trim(), error))?;
+    }
+
+    if !found {
+        let names = preset_names(&text);
+        return Err(format!("el preset '{}' no existe en '{}' (disponibles: {})", name, path, names.join(", ")));
+    }
+    Ok(())
+}
+
+/// Retorna los nombres de los presets de un archivo, o una lista vacía si no se puede leer.
+pub fn available_presets(path: &str) -> Vec<String> {
+    fs::read_to_string(path).map(|text| preset_names(&text)).unwrap_or_default()
+}
+
+fn preset_names(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']').map(|name| name.trim().to_string()))
+        .collect()
+}
+
+/// Construye la configuración a partir de las opciones de la línea de comandos.
+///
+/// # Retorno
+/// Retorna `Ok(None)` si se pidió `--help`, que ya queda impreso.
+pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<ColonyConfig>, String> {
+    let mut config = ColonyConfig::default();
+    let mut presets_file = DEFAULT_PRESETS_FILE.to_string();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            print_usage();
+            return Ok(None);
+        }
+        let flag = arg
+            .strip_prefix("--")
+            .ok_or_else(|| format!("argumento '{}' inesperado, las opciones empiezan por --", arg))?;
+        let (key, value) = match flag.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => (flag.to_string(), args.next().ok_or_else(|| format!("falta el valor de --{}", flag))?),
+        };
+
+        match key.as_str() {
+            "presets" => presets_file = value,
+            "preset" => apply_preset(&mut config, &presets_file, &value)?,
+            _ => config.set(&key, &value).map_err(|error| format!("--{}: {}", key, error))?,
+        }
+    }
+
+    config.validate()?;
+    Ok(Some(config))
+}
+
+/// Imprime las opciones disponibles con su valor por defecto.
+pub fn print_usage() {
+    let defaults = ColonyConfig::default();
+    println!("Uso: ejercicio6 [--presets ARCHIVO] [--preset NOMBRE] [--CLAVE VALOR]...");
+    println!("  --{:<11} Archivo de presets [{}]", "presets", DEFAULT_PRESETS_FILE);
+    println!("  --{:<11} Preset a cargar del archivo", "preset");
+    for (key, description) in KEYS {
+        println!("  --{:<11} {} [{}]", key, description, defaults.get(key));
+    }
+}
+
+/// Pregunta cada opción mostrando su valor actual, que se mantiene si la respuesta está
+/// vacía. Vuelve a preguntar mientras la respuesta no sea válida.
+pub fn prompt(config: &mut ColonyConfig) {
+    for (key, description) in KEYS {
+        loop {
+            let answer = read_line(&format!("{} [{}]: ", description, config.get(key)));
+            if answer.is_empty() {
+                break;
+            }
+            match config.set(key, &answer) {
+                Ok(()) => break,
+                Err(error) => println!("Valor inválido: {}. Intente de nuevo.", error),
+            }
+        }
+    }
+}
+
+/// Muestra un texto y lee una línea. Al final de la entrada retorna una línea vacía.
+pub fn read_line(label: &str) -> String {
+    print!("{}", label);
+    io::stdout().flush().unwrap();
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).unwrap_or(0);
+    answer.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::colony::JarPolicy;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    /// Escribe `text` en un archivo de presets temporal y retorna su ruta.
+    fn presets_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ejercicio6-{}-{}.ini", name, std::process::id()));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn set_parses_every_key() {
+        let mut config = ColonyConfig::default();
+        for (key, value) in [
+            ("bees", "12"),
+            ("capacities", " 3, 5,8 "),
+            ("eating-ms", "250"),
+            ("policy", "menos-lleno"),
+            ("seed", "7"),
+            ("feedings", "0"),
+            ("duration", "30"),
+        ] {
+            config.set(key, value).unwrap();
+        }
+        assert_eq!(config.bees, 12);
+        assert_eq!(config.capacities, vec![3, 5, 8]);
+        assert_eq!(config.eating_time, Duration::from_millis(250));
+        assert_eq!(config.policy, JarPolicy::LeastFull);
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.feedings, None);
+        assert_eq!(config.duration, Some(Duration::from_secs(30)));
+        for (key, _) in KEYS {
+            let mut copy = ColonyConfig::default();
+            copy.set(key, &config.get(key)).unwrap();
+            assert_eq!(copy.get(key), config.get(key));
+        }
+    }
+
+    #[test]
+    fn set_rejects_values_out_of_range_or_malformed() {
+        let mut config = ColonyConfig::default();
+        assert!(config.set("bees", "0").unwrap_err().contains("fuera de rango"));
+        assert!(config.set("bees", "10001").unwrap_err().contains("fuera de rango"));
+        assert!(config.set("capacities", "3,0").unwrap_err().contains("fuera de rango"));
+        assert!(config.set("eating-ms", "-5").unwrap_err().contains("no es un número"));
+        assert!(config.set("policy", "al-azar").unwrap_err().contains("no reconocida"));
+        assert!(config.set("seed", "x").unwrap_err().contains("semilla"));
+        assert!(config.set("colour", "1").unwrap_err().contains("desconocida"));
+        // Un valor rechazado no cambia la configuración.
+        assert_eq!(config.bees, ColonyConfig::default().bees);
+        assert_eq!(config.capacities, ColonyConfig::default().capacities);
+    }
+
+    #[test]
+    fn parse_in_range_accepts_both_bounds() {
+        assert_eq!(parse_in_range("1", 1, 3), Ok(1));
+        assert_eq!(parse_in_range("3", 1, 3), Ok(3));
+        assert!(parse_in_range("4", 1, 3).is_err());
+        assert!(parse_in_range("", 1, 3).is_err());
+    }
+
+    #[test]
+    fn apply_preset_loads_the_bundled_presets() {
+        let mut config = ColonyConfig::default();
+        apply_preset(&mut config, DEFAULT_PRESETS_FILE, "colony").unwrap();
+        assert_eq!(config.bees, 12);
+        assert_eq!(config.capacities, vec![3, 5, 8]);
+        assert_eq!(config.policy, JarPolicy::LeastFull);
+        assert_eq!(config.feedings, Some(30));
+        assert_eq!(available_presets(DEFAULT_PRESETS_FILE), ["small", "colony", "stress"]);
+    }
+
+    #[test]
+    fn apply_preset_reports_the_line_of_a_bad_value_and_unknown_presets() {
+        let path = presets_file("bad", "[a]\nbees = 2\n\n[b]\nbees = 0\n");
+        let path = path.to_str().unwrap();
+        let mut config = ColonyConfig::default();
+        apply_preset(&mut config, path, "a").unwrap();
+        assert_eq!(config.bees, 2);
+        assert!(apply_preset(&mut config, path, "b").unwrap_err().contains(":5: bees:"));
+        let error = apply_preset(&mut config, path, "c").unwrap_err();
+        assert!(error.contains("no existe") && error.contains("a, b"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn from_args_applies_options_in_order() {
+        let config = from_args(args("--preset small --bees=9 --capacities 2,2")).unwrap().unwrap();
+        assert_eq!(config.bees, 9);
+        assert_eq!(config.capacities, vec![2, 2]);
+        assert_eq!(config.eating_time, Duration::from_millis(800));
+        assert_eq!(config.feedings, Some(5));
+
+        let path = presets_file("args", "[mine]\nbees = 4\n");
+        let line = format!("--presets {} --preset mine", path.display());
+        assert_eq!(from_args(args(&line)).unwrap().unwrap().bees, 4);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn from_args_rejects_bad_arguments() {
+        assert!(from_args(args("bees 3")).unwrap_err().contains("inesperado"));
+        assert!(from_args(args("--bees")).unwrap_err().contains("falta el valor"));
+        assert!(from_args(args("--bees 0")).unwrap_err().starts_with("--bees:"));
+        assert!(from_args(args("--colour 1")).unwrap_err().contains("desconocida"));
+        assert!(from_args(args("--feedings 0")).unwrap_err().contains("no termina"));
+    }
+}
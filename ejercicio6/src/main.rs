@@ -1,7 +1,9 @@
 mod bear_bees;
+mod clock;
+mod colony;
+mod config;
 mod menu;
 
-#[tokio::main]
-async fn main() {
-    menu::run_menu().await;
+fn main() {
+    menu::run_menu();
 }
@@ -1,43 +1,51 @@
-use crate::bear_bees::{bee_task, HoneyJar};
-// use std::sync::Arc;
-use tokio::task;
+use crate::colony::{run_colony, ColonyConfig};
+use crate::config::{self, DEFAULT_PRESETS_FILE};
+use std::process;
 
-/// Muestra el menú y permite configurar la simulación.
-pub async fn run_menu() {
-    println!("Bienvenido a la simulación del oso y las abejas.");
-    println!("Ingrese el número de abejas:");
-    let mut bees_input = String::new();
-    std::io::stdin()
-        .read_line(&mut bees_input)
-        .expect("Error al leer la entrada.");
-    let num_bees: usize = bees_input
-        .trim()
-        .parse()
-        .expect("Debe ingresar un número válido.");
-
-    println!("Ingrese la capacidad del tarro de miel:");
-    let mut capacity_input = String::new();
-    std::io::stdin()
-        .read_line(&mut capacity_input)
-        .expect("Error al leer la entrada.");
-    let capacity: usize = capacity_input
-        .trim()
-        .parse()
-        .expect("Debe ingresar un número válido.");
+/// Muestra el menú y permite configurar la simulación. Si hay opciones en la línea de
+/// comandos, la simulación arranca directamente con ellas.
+pub fn run_menu() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        match config::from_args(args) {
+            Ok(Some(config)) => run_colony(config),
+            Ok(None) => {}
+            Err(error) => {
+                eprintln!("Error: {}.", error);
+                eprintln!("Use --help para ver las opciones disponibles.");
+                process::exit(2);
+            }
+        }
+        return;
+    }
 
-    let honey_jar = HoneyJar::new(capacity);
+    println!("Bienvenido a la simulación del oso y las abejas.");
+    let mut config = ColonyConfig::default();
 
-    // Inicia las tareas de las abejas.
-    let mut bee_tasks = Vec::new();
-    for bee_id in 1..=num_bees {
-        let jar_clone = honey_jar.clone();
-        bee_tasks.push(task::spawn(bee_task(jar_clone, bee_id)));
+    let presets = config::available_presets(DEFAULT_PRESETS_FILE);
+    if !presets.is_empty() {
+        loop {
+            let name = config::read_line(&format!(
+                "Preset ({}; Enter para configurar a mano): ",
+                presets.join(", ")
+            ));
+            if name.is_empty() {
+                break;
+            }
+            match config::apply_preset(&mut config, DEFAULT_PRESETS_FILE, &name) {
+                Ok(()) => break,
+                Err(error) => println!("{}. Intente de nuevo.", error),
+            }
+        }
     }
 
-    println!("Simulación en ejecución. Presione Ctrl+C para salir.");
-    let _ = tokio::join!(async {
-        for task in bee_tasks {
-            task.await.unwrap();
+    loop {
+        config::prompt(&mut config);
+        match config.validate() {
+            Ok(()) => break,
+            Err(error) => println!("Configuración inválida: {}.", error),
         }
-    });
+    }
+
+    run_colony(config);
 }
@@ -0,0 +1,169 @@
+use std::net::SocketAddr;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::task;
+use tokio::time::{sleep, Duration, Instant};
+use crate::server::{BUSY, HELLO, RATE_LIMITED, WELCOME};
+
+/// Cómo llegan los clientes: `bursts` ráfagas de `burst_size` conexiones simultáneas,
+/// separadas por `interval`. Las conexiones se reparten en orden entre `client_ids` clientes.
+#[derive(Clone, Copy, Debug)]
+pub struct LoadPlan {
+    pub bursts: usize,
+    pub burst_size: usize,
+    pub interval: Duration,
+    pub client_ids: usize,
+}
+
+impl LoadPlan {
+    /// Una sola ráfaga de `clients` conexiones, cada una de un cliente distinto.
+    pub fn single_burst(clients: usize) -> Self {
+        Self { bursts: 1, burst_size: clients, interval: Duration::ZERO, client_ids: clients }
+    }
+}
+
+/// Resultado de un cliente del generador de carga.
+enum Outcome {
+    Accepted(Duration), // Admitido, con el tiempo desde que empezó a conectarse hasta la bienvenida
+    Rejected,           // El servidor respondió `OCUPADO`
+    RateLimited,        // El servidor respondió `LIMITE`
+    Failed,             // No se pudo conectar o la respuesta no era válida
+}
+
+/// Reporte de una ronda de clientes contra el servidor.
+pub struct LoadReport {
+    pub clients: usize,       // Conexiones lanzadas
+    pub rejected: usize,      // Conexiones rechazadas por el servidor ocupado
+    pub rate_limited: usize,  // Conexiones rechazadas por el límite de tasa
+    pub failed: usize,        // Conexiones que no obtuvieron respuesta válida
+    pub elapsed: Duration,    // Tiempo hasta que terminó el último cliente
+    latencies: Vec<Duration>, // Latencia de admisión de cada conexión admitida, ordenada
+}
+
+impl LoadReport {
+    /// Clientes admitidos.
+    pub fn accepted(&self) -> usize {
+        self.latencies.len()
+    }
+
+    /// Latencia de admisión del percentil `p`.
+    pub fn percentile(&self, p: usize) -> Duration {
+        self.latencies
+            .get((self.latencies.len() * p / 100).min(self.latencies.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn mean_latency(&self) -> Duration {
+        self.latencies.iter().sum::<Duration>() / self.latencies.len().max(1) as u32
+    }
+
+    pub fn max_latency(&self) -> Duration {
+        self.latencies.last().copied().unwrap_or_default()
+    }
+
+    /// Imprime el reporte en una tabla.
+    pub fn print(&self) {
+        println!("===== Generador de carga: {} clientes en {:.3}s =====", self.clients, self.elapsed.as_secs_f64());
+        println!("{:>10} {:>10} {:>12} {:>8}", "Admitidos", "Rechazados", "Límite tasa", "Fallidos");
+        println!("{:>10} {:>10} {:>12} {:>8}", self.accepted(), self.rejected, self.rate_limited, self.failed);
+        println!(
+            "Latencia de admisión: media {:.3}ms, p50 {:.3}ms, p99 {:.3}ms, máx {:.3}ms",
+            self.mean_latency().as_secs_f64() * 1000.0,
+            self.percentile(50).as_secs_f64() * 1000.0,
+            self.percentile(99).as_secs_f64() * 1000.0,
+            self.max_latency().as_secs_f64() * 1000.0
+        );
+    }
+}
+
+/// Abre las conexiones de `plan` contra `addr` y espera a que terminen todas las sesiones.
+///
+/// # Retorno
+/// Retorna un `LoadReport` con las conexiones admitidas, rechazadas y la latencia de admisión.
+pub async fn generate_load(addr: SocketAddr, plan: &LoadPlan) -> LoadReport {
+    let start = Instant::now();
+    let mut tasks = Vec::new();
+    for burst in 0..plan.bursts {
+        if burst > 0 {
+            sleep(plan.interval).await;
+        }
+        for connection in 0..plan.burst_size {
+            let client = format!("cliente-{}", (burst * plan.burst_size + connection) % plan.client_ids.max(1) + 1);
+            tasks.push(task::spawn(connect(addr, client)));
+        }
+    }
+
+    collect_report(tasks, start).await
+}
+
+/// Abre las conexiones de cada cliente de `clients`, todas las de un cliente a la vez y
+/// `stagger` antes que las del siguiente, y espera a que terminen todas las sesiones.
+///
+/// # Retorno
+/// Retorna un `LoadReport` con las conexiones admitidas, rechazadas y la latencia de admisión.
+pub async fn generate_clients(addr: SocketAddr, clients: &[(&str, usize)], stagger: Duration) -> LoadReport {
+    let start = Instant::now();
+    let mut tasks = Vec::new();
+    for (index, &(client, connections)) in clients.iter().enumerate() {
+        if index > 0 {
+            sleep(stagger).await;
+        }
+        for _ in 0..connections {
+            tasks.push(task::spawn(connect(addr, client.to_string())));
+        }
+    }
+    collect_report(tasks, start).await
+}
+
+/// Espera a las conexiones lanzadas desde `start` y arma su reporte.
+async fn collect_report(tasks: Vec<task::JoinHandle<Outcome>>, start: Instant) -> LoadReport {
+    let mut report = LoadReport {
+        clients: tasks.len(),
+        rejected: 0,
+        rate_limited: 0,
+        failed: 0,
+        elapsed: Duration::ZERO,
+        latencies: Vec::new(),
+    };
+    for client in tasks {
+        match client.await.unwrap_or(Outcome::Failed) {
+            Outcome::Accepted(latency) => report.latencies.push(latency),
+            Outcome::Rejected => report.rejected += 1,
+            Outcome::RateLimited => report.rate_limited += 1,
+            Outcome::Failed => report.failed += 1,
+        }
+    }
+    report.elapsed = start.elapsed();
+    report.latencies.sort();
+    report
+}
+
+/// Un cliente: se conecta, se presenta, espera la respuesta del servidor y, si es
+/// admitido, se queda conectado hasta que el servidor cierra la sesión.
+async fn connect(addr: SocketAddr, client: String) -> Outcome {
+    let started = Instant::now();
+    let Ok(mut stream) = TcpStream::connect(addr).await else {
+        return Outcome::Failed;
+    };
+    if stream.write_all(format!("{} {}\n", HELLO, client).as_bytes()).await.is_err() {
+        return Outcome::Failed;
+    }
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    if reader.read_line(&mut line).await.is_err() {
+        return Outcome::Failed;
+    }
+
+    match line.split_whitespace().next() {
+        Some(word) if word == WELCOME => {
+            let latency = started.elapsed();
+            let mut rest = Vec::new();
+            let _ = reader.read_to_end(&mut rest).await;
+            Outcome::Accepted(latency)
+        }
+        Some(word) if word == BUSY => Outcome::Rejected,
+        Some(word) if word == RATE_LIMITED => Outcome::RateLimited,
+        _ => Outcome::Failed,
+    }
+}
@@ -0,0 +1,211 @@
+/*
+Admisión justa entre clientes con colas por cliente y turno rotativo con déficit (DRR).
+
+Con un semáforo, las conexiones entran en el orden en que piden el permiso, así que un
+cliente que abre muchas conexiones de golpe deja a los demás detrás de toda su ráfaga.
+Aquí cada cliente tiene su propia cola de conexiones en espera. Cuando se libera un lugar,
+se recorre en ronda la lista de clientes con conexiones esperando: al llegarle el turno,
+un cliente recibe un crédito igual a su peso y entrega un lugar por crédito antes de pasar
+al siguiente. Mientras haya competencia, la fracción de lugares de cada cliente es
+proporcional a su peso, sin importar cuántas conexiones tenga esperando.
+*/
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Peso de `client` según `weights`. Los clientes que no aparecen, y los de peso 0, pesan 1.
+pub fn weight_of(weights: &HashMap<String, u32>, client: &str) -> u32 {
+    weights.get(client).map_or(1, |&weight| weight.max(1))
+}
+
+/// Lugar asignado a una conexión. Al soltarlo pasa a la siguiente conexión según el turno.
+pub struct FairPermit {
+    gate: Option<Arc<FairGate>>, // `None` si el permiso nunca llegó a su conexión
+}
+
+impl FairPermit {
+    fn new(gate: &Arc<FairGate>) -> Self {
+        Self { gate: Some(Arc::clone(gate)) }
+    }
+}
+
+impl Drop for FairPermit {
+    fn drop(&mut self) {
+        if let Some(gate) = self.gate.take() {
+            gate.release();
+        }
+    }
+}
+
+/// Conexiones en espera de un cliente.
+struct ClientQueue {
+    weight: u32,                                    // Lugares por turno
+    deficit: u32,                                   // Lugares que le quedan en el turno actual
+    waiters: VecDeque<oneshot::Sender<FairPermit>>, // Conexiones esperando, en orden de llegada
+}
+
+/// Estado de la puerta, protegido por su mutex.
+struct GateState {
+    free: usize,                          // Lugares sin usar
+    queues: HashMap<String, ClientQueue>, // Cola de cada cliente con conexiones esperando
+    ring: VecDeque<String>,               // Clientes con conexiones esperando, en orden de turno
+}
+
+/// Puerta de admisión con `slots` lugares repartidos entre clientes según su peso.
+pub struct FairGate {
+    state: Mutex<GateState>,
+    weights: HashMap<String, u32>, // Peso de cada cliente; los que no aparecen pesan 1
+}
+
+impl FairGate {
+    pub fn new(slots: usize, weights: HashMap<String, u32>) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(GateState { free: slots, queues: HashMap::new(), ring: VecDeque::new() }),
+            weights,
+        })
+    }
+
+    /// Espera un lugar para una conexión de `client`.
+    ///
+    /// Si hay lugar libre y nadie esperando, entra enseguida. Si no, se pone al final de la
+    /// cola de su cliente y espera a que quien libere un lugar se lo entregue. Si la espera se
+    /// cancela después de la entrega, el lugar vuelve a la puerta al soltarse el permiso.
+    pub async fn acquire(self: &Arc<Self>, client: &str) -> FairPermit {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.free > 0 && state.ring.is_empty() {
+                state.free -= 1;
+                return FairPermit::new(self);
+            }
+
+            let (sender, receiver) = oneshot::channel();
+            let weight = weight_of(&self.weights, client);
+            let queue = state
+                .queues
+                .entry(client.to_string())
+                .or_insert_with(|| ClientQueue { weight, deficit: 0, waiters: VecDeque::new() });
+            queue.waiters.push_back(sender);
+            if queue.waiters.len() == 1 {
+                state.ring.push_back(client.to_string());
+            }
+            receiver
+        };
+        receiver.await.expect("la puerta de admisión se destruyó con conexiones esperando")
+    }
+
+    /// Entrega el lugar liberado a la siguiente conexión según el turno, o lo deja libre.
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock().unwrap();
+        while let Some(waiter) = Self::next_waiter(&mut state) {
+            match waiter.send(FairPermit::new(self)) {
+                Ok(()) => return,
+                // La conexión dejó de esperar; el lugar sigue siendo de quien lo libera.
+                Err(mut permit) => permit.gate = None,
+            }
+        }
+        state.free += 1;
+    }
+
+    /// Saca la siguiente conexión en espera según el turno rotativo con déficit.
+    fn next_waiter(state: &mut GateState) -> Option<oneshot::Sender<FairPermit>> {
+        let client = state.ring.front()?.clone();
+        let queue = state.queues.get_mut(&client).unwrap();
+        if queue.deficit == 0 {
+            queue.deficit = queue.weight;
+        }
+        let waiter = queue.waiters.pop_front().unwrap();
+        queue.deficit -= 1;
+        if queue.waiters.is_empty() {
+            // Un cliente sin conexiones esperando sale de la ronda y no acumula crédito.
+            state.ring.pop_front();
+            state.queues.remove(&client);
+        } else if queue.deficit == 0 {
+            state.ring.rotate_left(1);
+        }
+        Some(waiter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+    use tokio::task;
+
+    fn weights(pairs: &[(&str, u32)]) -> HashMap<String, u32> {
+        pairs.iter().map(|&(client, weight)| (client.to_string(), weight)).collect()
+    }
+
+    /// Ocupa el único lugar, pone en espera las conexiones de `clients` en ese orden y luego
+    /// libera el lugar. Cada conexión suelta su lugar apenas lo recibe.
+    ///
+    /// Los pedidos se sondean a mano, sin runtime: el orden de llegada es el de `clients` y
+    /// cada entrega ocurre dentro de `release`, así que el resultado no depende del planificador.
+    ///
+    /// # Retorno
+    /// Retorna los clientes en el orden en que recibieron el lugar.
+    fn grant_order(weights: HashMap<String, u32>, clients: &[(&'static str, usize)]) -> Vec<&'static str> {
+        let gate = FairGate::new(1, weights);
+        let mut context = Context::from_waker(Waker::noop());
+        let mut holder = Box::pin(gate.acquire("dueño"));
+        let Poll::Ready(holder) = holder.as_mut().poll(&mut context) else {
+            panic!("la puerta vacía no admitió la primera conexión");
+        };
+
+        let mut waiting = vec![];
+        for &(client, connections) in clients {
+            for _ in 0..connections {
+                let mut acquire = Box::pin(gate.acquire(client));
+                assert!(acquire.as_mut().poll(&mut context).is_pending(), "{} entró con la puerta llena", client);
+                waiting.push((client, acquire));
+            }
+        }
+
+        drop(holder);
+        let mut order = vec![];
+        while !waiting.is_empty() {
+            // Al soltarse, el permiso recién recibido pasa a la siguiente conexión del turno.
+            let granted = waiting
+                .iter_mut()
+                .position(|(_, acquire)| acquire.as_mut().poll(&mut context).is_ready())
+                .expect("se liberó el lugar y ninguna conexión en espera lo recibió");
+            order.push(waiting.remove(granted).0);
+        }
+        assert!(gate.state.lock().unwrap().queues.is_empty(), "quedaron colas de clientes sin conexiones esperando");
+        order
+    }
+
+    #[test]
+    fn a_greedy_client_only_gets_its_turn() {
+        let order = grant_order(weights(&[]), &[("voraz", 6), ("a", 2), ("b", 2), ("c", 2)]);
+        assert_eq!(
+            order,
+            ["voraz", "a", "b", "c", "voraz", "a", "b", "c", "voraz", "voraz", "voraz", "voraz"]
+        );
+    }
+
+    #[test]
+    fn shares_follow_the_weights() {
+        let order = grant_order(weights(&[("pesado", 3)]), &[("pesado", 9), ("liviano", 3)]);
+        // Mientras los dos esperan, cada ronda da 3 lugares a `pesado` y 1 a `liviano`.
+        assert_eq!(order[..8], ["pesado", "pesado", "pesado", "liviano", "pesado", "pesado", "pesado", "liviano"]);
+    }
+
+    #[tokio::test]
+    async fn a_cancelled_waiter_does_not_lose_the_slot() {
+        let gate = FairGate::new(1, HashMap::new());
+        let holder = gate.acquire("a").await;
+        let cancelled = task::spawn({
+            let gate = Arc::clone(&gate);
+            async move { gate.acquire("b").await }
+        });
+        task::yield_now().await;
+        cancelled.abort();
+        let _ = cancelled.await;
+
+        drop(holder);
+        let _permit = gate.acquire("c").await;
+        assert_eq!(gate.state.lock().unwrap().free, 0);
+    }
+}
@@ -1,7 +1,12 @@
+mod client;
+mod fair_queue;
 mod menu;
 mod server;
+mod spooler;
 mod printer;
+mod rate_limit;
 mod user;
+mod wait_stats;
 
 use std::io;
 
@@ -15,6 +20,9 @@ async fn main() {
         println!("1. Problema 17");
         println!("2. Problema 18 A");
         println!("3. Problema 18 B");
+        println!("4. Problema 18 con cola de impresión");
+        println!("5. Problema 17 con límite de tasa");
+        println!("6. Problema 17 con admisión justa");
         println!("0. Salir");
 
         answer.clear();
@@ -26,6 +34,9 @@ async fn main() {
             "1" => menu::problema_17().await,
             "2" => menu::problema_18_a(),
             "3" => menu::problema_18_b(),
+            "4" => menu::problema_18_spooler(),
+            "5" => menu::problema_17_rate_limit().await,
+            "6" => menu::problema_17_fairness().await,
             "0" => {
                 println!("Hasta luego.");
                 break;
@@ -1,13 +1,46 @@
-use crate::server;
+use std::io::{self, Write};
+use std::str::FromStr;
+use crate::server::{self, Admission, ServerConfig};
 use crate::printer;
+use crate::spooler;
 use crate::user;
 
 /// Funcion del ejercicio 17
 /// 
 /// Para este problema un servidor puede permitir
-/// N conexiones simultaneas
+/// N conexiones simultaneas. Se levanta un servidor TCP
+/// en localhost y un generador de carga se conecta a él
 pub async fn problema_17() {
-    server::start_connection().await;
+    let mut config = ServerConfig::default();
+    config.port = read_value("Puerto (0 = cualquiera libre)", config.port);
+    config.max_connections = loop {
+        // Sin permisos el semáforo no admite a nadie y los clientes en cola esperan para siempre.
+        match read_value("Conexiones simultáneas", config.max_connections) {
+            0 => println!("Debe haber al menos una conexión simultánea."),
+            max_connections => break max_connections,
+        }
+    };
+    if read_value("¿Rechazar a los clientes que no caben en vez de ponerlos en cola? (s/n)", 'n') == 's' {
+        config.admission = Admission::Refuse;
+    }
+    let clients = read_value("Clientes", server::TOTAL_USERS);
+    server::start_connection(config, clients).await;
+}
+
+/// Funcion del ejercicio 17 con límite de tasa
+///
+/// Compara el semáforo de conexiones solo y combinado con
+/// limitadores de tasa ante llegadas en ráfagas
+pub async fn problema_17_rate_limit() {
+    server::compare_rate_limits().await;
+}
+
+/// Funcion del ejercicio 17 con admisión justa
+///
+/// Compara la cola del semáforo con colas por cliente
+/// cuando un cliente voraz compite con otros livianos
+pub async fn problema_17_fairness() {
+    server::compare_fairness().await;
 }
 
 /// Funcion del ejercicio 18 A
@@ -22,4 +55,32 @@ pub fn problema_18_a() {
 /// Para este problema ademas hay prioridad por usuario
 pub fn problema_18_b() {
     user::use_printer_with_priority();
-}
\ No newline at end of file
+}
+
+/// Funcion del ejercicio 18 con cola de impresión
+///
+/// Los usuarios envían trabajos a un spooler que los reparte
+/// entre impresoras con distintas capacidades y velocidades
+pub fn problema_18_spooler() {
+    spooler::run_spooler();
+}
+
+/// Pide un valor al usuario, usando el valor por defecto si la respuesta está vacía o no es válida.
+///
+/// # Parámetros
+/// - `label`: texto que se muestra al usuario.
+/// - `default`: valor usado si el usuario solo presiona Enter.
+fn read_value<T: FromStr + std::fmt::Display>(label: &str, default: T) -> T {
+    print!("{} [{}]: ", label, default);
+    io::stdout().flush().unwrap();
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).unwrap_or(0);
+    match answer.trim() {
+        "" => default,
+        text => text.parse().unwrap_or_else(|_| {
+            println!("Valor inválido, se usa {}.", default);
+            default
+        }),
+    }
+}
@@ -0,0 +1,255 @@
+/*
+Limitadores de tasa. El semáforo del servidor limita cuántas sesiones hay abiertas a la vez;
+un limitador de tasa limita cuántas llegan por unidad de tiempo. Son problemas distintos: con
+sesiones muy cortas, una ráfaga enorme nunca llena el semáforo pero igual satura al servidor
+aceptando conexiones, y con sesiones largas pocas llegadas bastan para llenarlo.
+
+- Cubeta de fichas: cada petición gasta una ficha y las fichas se reponen a ritmo constante
+  hasta la capacidad de la cubeta. Admite ráfagas de hasta `burst` peticiones seguidas.
+- Ventana deslizante: admite como mucho `limit` peticiones en cualquier intervalo de
+  duración `window`, recordando el instante de cada petición admitida.
+
+Cada limitador se puede aplicar a todo el servidor o por separado a cada cliente.
+*/
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Limitador de tasa de un solo flujo de peticiones.
+pub trait RateLimiter: Send {
+    /// Intenta admitir una petición en el instante `now`.
+    ///
+    /// # Retorno
+    /// Retorna `Ok` si la petición entra, o `Err` con el tiempo que falta para que entre una.
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration>;
+}
+
+/// Cubeta de fichas.
+pub struct TokenBucket {
+    capacity: f64,   // Fichas como máximo, es decir, la ráfaga admitida
+    per_second: f64, // Fichas que se reponen por segundo
+    tokens: f64,     // Fichas disponibles en `last`
+    last: Instant,   // Última vez que se repusieron fichas
+}
+
+impl TokenBucket {
+    /// Crea una cubeta llena.
+    ///
+    /// # Parámetros
+    /// - `burst`: capacidad de la cubeta. Debe ser al menos 1: una cubeta sin capacidad nunca
+    ///   junta la ficha que necesita una petición.
+    /// - `per_second`: fichas que se reponen por segundo. Debe ser positivo: sin reposición
+    ///   no se puede calcular cuánto falta para la próxima ficha.
+    /// - `now`: instante de creación.
+    pub fn new(burst: u32, per_second: f64, now: Instant) -> Self {
+        assert!(burst > 0, "la cubeta debe admitir al menos 1 ficha");
+        assert!(per_second > 0.0 && per_second.is_finite(), "la cubeta debe reponer más de 0 fichas por segundo");
+        Self { capacity: burst as f64, per_second, tokens: burst as f64, last: now }
+    }
+}
+
+impl RateLimiter for TokenBucket {
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.per_second))
+        }
+    }
+}
+
+/// Ventana deslizante con el registro de las peticiones admitidas.
+pub struct SlidingWindow {
+    limit: usize,                // Peticiones admitidas como máximo en cada ventana
+    window: Duration,            // Duración de la ventana
+    admitted: VecDeque<Instant>, // Instantes de las peticiones admitidas en la última ventana
+}
+
+impl SlidingWindow {
+    /// Crea una ventana sin peticiones admitidas.
+    ///
+    /// # Parámetros
+    /// - `limit`: peticiones admitidas como máximo en cada ventana. Debe ser al menos 1.
+    /// - `window`: duración de la ventana.
+    pub fn new(limit: usize, window: Duration) -> Self {
+        assert!(limit > 0, "la ventana debe admitir al menos 1 petición");
+        Self { limit, window, admitted: VecDeque::with_capacity(limit) }
+    }
+}
+
+impl RateLimiter for SlidingWindow {
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        while self.admitted.front().is_some_and(|&at| now.saturating_duration_since(at) >= self.window) {
+            self.admitted.pop_front();
+        }
+        if self.admitted.len() < self.limit {
+            self.admitted.push_back(now);
+            Ok(())
+        } else {
+            // La petición más antigua de la ventana es la primera en salir de ella.
+            Err((self.admitted[0] + self.window).saturating_duration_since(now))
+        }
+    }
+}
+
+/// Algoritmo de limitación y sus parámetros.
+#[derive(Clone, Copy, Debug)]
+pub enum Algorithm {
+    TokenBucket { burst: u32, per_second: f64 },
+    SlidingWindow { limit: usize, window: Duration },
+}
+
+impl Algorithm {
+    fn build(&self, now: Instant) -> Box<dyn RateLimiter> {
+        match *self {
+            Algorithm::TokenBucket { burst, per_second } => Box::new(TokenBucket::new(burst, per_second, now)),
+            Algorithm::SlidingWindow { limit, window } => Box::new(SlidingWindow::new(limit, window)),
+        }
+    }
+}
+
+/// A quién se aplica el límite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    Global,    // Un solo limitador para todo el servidor
+    PerClient, // Un limitador por identificador de cliente
+}
+
+/// Qué hace el servidor con una petición que supera el límite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnLimit {
+    Delay { max: Duration }, // La demora hasta que entre, si la espera total no pasa de `max`
+    Reject,                  // La rechaza enseguida
+}
+
+/// Configuración de la limitación de tasa del servidor.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitConfig {
+    pub algorithm: Algorithm,
+    pub scope: Scope,
+    pub on_limit: OnLimit,
+}
+
+impl fmt::Display for RateLimitConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.algorithm {
+            Algorithm::TokenBucket { burst, per_second } => write!(f, "cubeta {}/s ráfaga {}", per_second, burst)?,
+            Algorithm::SlidingWindow { limit, window } => write!(f, "ventana {} en {}ms", limit, window.as_millis())?,
+        }
+        write!(f, "{}", if self.scope == Scope::PerClient { " por cliente" } else { " global" })?;
+        match self.on_limit {
+            OnLimit::Delay { .. } => write!(f, ", demorar"),
+            OnLimit::Reject => write!(f, ", rechazar"),
+        }
+    }
+}
+
+/// Limitadores del servidor: uno global o uno por cliente, creado la primera vez que llega.
+pub struct Limiters {
+    config: RateLimitConfig,
+    limiters: Mutex<HashMap<String, Box<dyn RateLimiter>>>,
+}
+
+impl Limiters {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config, limiters: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn on_limit(&self) -> OnLimit {
+        self.config.on_limit
+    }
+
+    /// Intenta admitir una petición de `client` en el instante `now`.
+    ///
+    /// # Retorno
+    /// Retorna `Ok` si la petición entra, o `Err` con el tiempo que falta para que entre una.
+    pub fn check(&self, client: &str, now: Instant) -> Result<(), Duration> {
+        let key = match self.config.scope {
+            Scope::Global => "",
+            Scope::PerClient => client,
+        };
+        let mut limiters = self.limiters.lock().unwrap();
+        limiters
+            .entry(key.to_string())
+            .or_insert_with(|| self.config.algorithm.build(now))
+            .try_acquire(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_admits_a_burst_then_the_refill_rate() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(3, 10.0, start);
+        for _ in 0..3 {
+            assert_eq!(bucket.try_acquire(start), Ok(()));
+        }
+        let wait = bucket.try_acquire(start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(100));
+        assert_eq!(bucket.try_acquire(start + wait), Ok(()));
+        assert!(bucket.try_acquire(start + wait).is_err());
+        // Tras un rato sin peticiones la cubeta vuelve a estar llena, y no más que llena.
+        let later = start + Duration::from_secs(5);
+        for _ in 0..3 {
+            assert_eq!(bucket.try_acquire(later), Ok(()));
+        }
+        assert!(bucket.try_acquire(later).is_err());
+    }
+
+    #[test]
+    fn sliding_window_counts_requests_in_any_interval() {
+        let start = Instant::now();
+        let window = Duration::from_secs(1);
+        let mut limiter = SlidingWindow::new(2, window);
+        assert_eq!(limiter.try_acquire(start), Ok(()));
+        assert_eq!(limiter.try_acquire(start + Duration::from_millis(600)), Ok(()));
+        assert_eq!(limiter.try_acquire(start + Duration::from_millis(900)), Err(Duration::from_millis(100)));
+        assert_eq!(limiter.try_acquire(start + window), Ok(()));
+        assert_eq!(limiter.try_acquire(start + Duration::from_millis(1500)), Err(Duration::from_millis(100)));
+    }
+
+    #[test]
+    #[should_panic(expected = "más de 0 fichas por segundo")]
+    fn token_bucket_rejects_a_zero_refill_rate() {
+        TokenBucket::new(3, 0.0, Instant::now());
+    }
+
+    #[test]
+    #[should_panic(expected = "al menos 1 ficha")]
+    fn token_bucket_rejects_a_zero_burst() {
+        TokenBucket::new(0, 10.0, Instant::now());
+    }
+
+    #[test]
+    #[should_panic(expected = "al menos 1 petición")]
+    fn sliding_window_rejects_a_zero_limit() {
+        SlidingWindow::new(0, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn per_client_limits_are_independent() {
+        let now = Instant::now();
+        let config = |scope| RateLimitConfig {
+            algorithm: Algorithm::SlidingWindow { limit: 1, window: Duration::from_secs(1) },
+            scope,
+            on_limit: OnLimit::Reject,
+        };
+
+        let per_client = Limiters::new(config(Scope::PerClient));
+        assert!(per_client.check("a", now).is_ok());
+        assert!(per_client.check("b", now).is_ok());
+        assert!(per_client.check("a", now).is_err());
+
+        let global = Limiters::new(config(Scope::Global));
+        assert!(global.check("a", now).is_ok());
+        assert!(global.check("b", now).is_err());
+    }
+}
@@ -1,55 +1,597 @@
-use std::sync::Arc;
-use tokio::sync::Semaphore;
-use tokio::time::{sleep, Duration};
+/*
+Servidor TCP real en localhost. Cada conexión aceptada es una sesión de cliente, y el semáforo
+de `MAX_CONNECTION` permisos limita cuántas sesiones hay a la vez (control de admisión).
+Opcionalmente un limitador de tasa (`rate_limit`) limita además cuántas llegan por segundo.
+
+Protocolo, una línea de texto por mensaje:
+- El cliente se presenta con `HOLA <cliente>`, que identifica al cliente para los límites
+  por cliente.
+- Si la conexión supera el límite de tasa, el servidor la demora hasta que entre o responde
+  `LIMITE` y cierra, según la configuración.
+- Si el cliente es admitido, el servidor responde `BIENVENIDO <n>`, mantiene la sesión durante
+  `session_time` y cierra la conexión.
+- Si no hay permisos libres y el servidor rechaza clientes, responde `OCUPADO` y cierra.
+  Si en cambio los pone en cola, el cliente espera conectado hasta recibir la bienvenida.
+  Con admisión justa la espera es en la cola de su cliente (`fair_queue`).
+*/
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::task;
+use tokio::time::{sleep, timeout, Duration, Instant};
+use crate::client::{generate_clients, generate_load, LoadPlan, LoadReport};
+use crate::fair_queue::{weight_of, FairGate, FairPermit};
+use crate::rate_limit::{Algorithm, Limiters, OnLimit, RateLimitConfig, Scope};
+use crate::wait_stats::WaitStats;
 
-const  MAX_CONNECTION: usize = 3;
-const TOTAL_USERS: usize = 10;
+pub const MAX_CONNECTION: usize = 3;
+pub const TOTAL_USERS: usize = 10;
+/// Puerto en el que escucha el servidor si no se indica otro.
+pub const DEFAULT_PORT: u16 = 7878;
+/// Tiempo que dura cada sesión admitida.
+pub const SESSION_TIME: Duration = Duration::from_secs(2);
+/// Tiempo que espera el servidor a que el cliente se presente.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(1);
 
-/// Inicia la simulación de conexiones concurrentes al servidor.
-///
-/// Esta función simula un sistema donde hasta `MAX_CONNECTION` usuarios pueden conectarse al servidor 
-/// al mismo tiempo. Los usuarios adicionales deben esperar a que se libere un permiso antes de continuar.
-/// 
-/// @Arc::new(Semaphore::new()) Se utiliza para inicializar un semáforo que controla el acceso simultáneo.
-/// @task::spawn() Lanza tareas asíncronas que representan las conexiones de los usuarios.
-pub async fn start_connection() {
-    let semaphore = Arc::new(Semaphore::new(MAX_CONNECTION));
-    
-    let mut tasks = vec![];
-
-    for i in 1..=TOTAL_USERS {
-        let sem_clone = Arc::clone(&semaphore);
-        tasks.push(task::spawn(async move {
-            handle_connection(i, sem_clone).await;
-        }));
-    }
-    
-    // Esperar a que todas las tareas terminen
-    for t in tasks {
-        t.await.unwrap();
-    }
-}
-
-/// Maneja una conexión simulada para un usuario específico.
+/// Primera palabra del saludo del cliente.
+pub const HELLO: &str = "HOLA";
+/// Primera palabra de la respuesta a un cliente admitido.
+pub const WELCOME: &str = "BIENVENIDO";
+/// Respuesta a un cliente rechazado.
+pub const BUSY: &str = "OCUPADO";
+/// Respuesta a un cliente que superó el límite de tasa.
+pub const RATE_LIMITED: &str = "LIMITE";
+
+/// Qué hace el servidor con un cliente que llega cuando no quedan permisos.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Admission {
+    Queue,  // El cliente espera conectado a que se libere un permiso
+    Refuse, // El cliente recibe `OCUPADO` y se cierra la conexión
+    Fair,   // El cliente espera en su propia cola; los lugares se reparten por peso
+}
+
+impl fmt::Display for Admission {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Admission::Queue => write!(f, "cola"),
+            Admission::Refuse => write!(f, "rechazar"),
+            Admission::Fair => write!(f, "justa"),
+        }
+    }
+}
+
+/// Configuración del servidor.
+#[derive(Clone, Debug)]
+pub struct ServerConfig {
+    pub port: u16,                           // Puerto local; 0 elige uno libre
+    pub max_connections: usize,              // Sesiones simultáneas como máximo
+    pub admission: Admission,                // Qué hacer con los clientes que no caben
+    pub session_time: Duration,              // Duración de cada sesión admitida
+    pub rate_limit: Option<RateLimitConfig>, // Límite de llegadas, si hay
+    pub weights: HashMap<String, u32>,       // Peso de cada cliente con `Admission::Fair`
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            max_connections: MAX_CONNECTION,
+            admission: Admission::Queue,
+            session_time: SESSION_TIME,
+            rate_limit: None,
+            weights: HashMap::new(),
+        }
+    }
+}
+
+/// Contadores del servidor, compartidos con las tareas de cada sesión.
+#[derive(Debug, Default)]
+pub struct ServerStats {
+    active: AtomicUsize,                        // Sesiones abiertas ahora
+    peak: AtomicUsize,                          // Máximo de sesiones abiertas a la vez
+    served: AtomicUsize,                        // Sesiones terminadas
+    refused: AtomicUsize,                       // Clientes rechazados por falta de permisos
+    queued: AtomicUsize,                        // Clientes que esperaron en la cola del semáforo
+    delayed: AtomicUsize,                       // Clientes demorados por el límite de tasa
+    rate_limited: AtomicUsize,                  // Clientes rechazados por el límite de tasa
+    admitted: AtomicUsize,                      // Sesiones admitidas hasta ahora
+    waits: Mutex<BTreeMap<String, WaitStats>>,  // Espera por un lugar de cada cliente
+    last_turns: Mutex<BTreeMap<String, usize>>, // Puesto de la última admisión de cada cliente, desde 1
+}
+
+impl ServerStats {
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    pub fn served(&self) -> usize {
+        self.served.load(Ordering::SeqCst)
+    }
+
+    pub fn refused(&self) -> usize {
+        self.refused.load(Ordering::SeqCst)
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    pub fn delayed(&self) -> usize {
+        self.delayed.load(Ordering::SeqCst)
+    }
+
+    pub fn rate_limited(&self) -> usize {
+        self.rate_limited.load(Ordering::SeqCst)
+    }
+
+    /// Retorna las admisiones y esperas de cada cliente.
+    pub fn client_waits(&self) -> BTreeMap<String, WaitStats> {
+        self.waits.lock().unwrap().clone()
+    }
+
+    /// Retorna, para cada cliente, el puesto de su última admisión entre todas las del servidor.
+    pub fn last_turns(&self) -> BTreeMap<String, usize> {
+        self.last_turns.lock().unwrap().clone()
+    }
+
+    fn record_admission(&self, client: &str, waited: Duration) {
+        let turn = self.admitted.fetch_add(1, Ordering::SeqCst) + 1;
+        self.waits.lock().unwrap().entry(client.to_string()).or_default().record(waited);
+        self.last_turns.lock().unwrap().insert(client.to_string(), turn);
+    }
+
+    /// Registra una sesión nueva y retorna cuántas hay abiertas con ella.
+    fn session_started(&self) -> usize {
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(active, Ordering::SeqCst);
+        active
+    }
+
+    fn session_ended(&self) {
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        self.served.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Servidor TCP que admite como mucho `max_connections` sesiones simultáneas.
+pub struct Server {
+    listener: TcpListener,
+    semaphore: Arc<Semaphore>,       // Un permiso por sesión, con `Queue` y `Refuse`
+    gate: Arc<FairGate>,             // Un lugar por sesión, con `Fair`
+    limiters: Option<Arc<Limiters>>, // Límite de tasa, si hay
+    config: Arc<ServerConfig>,
+    stats: Arc<ServerStats>,
+}
+
+impl Server {
+    /// Abre el puerto de `config` en localhost.
+    ///
+    /// # Retorno
+    /// Retorna el servidor listo para aceptar conexiones, o el error si el puerto no se puede usar.
+    pub async fn bind(config: ServerConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", config.port)).await?;
+        Ok(Self {
+            listener,
+            semaphore: Arc::new(Semaphore::new(config.max_connections)),
+            gate: FairGate::new(config.max_connections, config.weights.clone()),
+            limiters: config.rate_limit.map(|rate_limit| Arc::new(Limiters::new(rate_limit))),
+            config: Arc::new(config),
+            stats: Arc::new(ServerStats::default()),
+        })
+    }
+
+    /// Dirección en la que escucha, útil si se pidió el puerto 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn stats(&self) -> Arc<ServerStats> {
+        Arc::clone(&self.stats)
+    }
+
+    /// Acepta conexiones hasta que se cancele la tarea. Cada conexión se atiende en su propia tarea.
+    pub async fn run(self) {
+        let mut next_id = 0;
+        loop {
+            let (stream, _) = match self.listener.accept().await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    println!("Error al aceptar una conexión: {}", error);
+                    continue;
+                }
+            };
+            next_id += 1;
+            task::spawn(admit(
+                next_id,
+                stream,
+                Arc::clone(&self.semaphore),
+                Arc::clone(&self.gate),
+                self.limiters.clone(),
+                Arc::clone(&self.config),
+                Arc::clone(&self.stats),
+            ));
+        }
+    }
+}
+
+/// Decide si una conexión entra: primero el límite de tasa y después el semáforo.
 ///
-/// Representa el manejo de una conexión al servidor para un usuario individual.
-/// Cada usuario intenta adquirir un permiso antes de iniciar la conexión.
+/// @limiters.check() Con `OnLimit::Delay`, espera lo que indique el limitador y vuelve a probar.
+/// @semaphore.try_acquire_owned() Con `Admission::Refuse`, toma un permiso sin esperar o rechaza.
+/// @semaphore.acquire_owned() Con `Admission::Queue`, si no hay permiso libre el cliente lo espera conectado.
+/// @gate.acquire() Con `Admission::Fair`, el cliente espera conectado en la cola de su cliente.
+async fn admit(
+    id: usize,
+    stream: TcpStream,
+    semaphore: Arc<Semaphore>,
+    gate: Arc<FairGate>,
+    limiters: Option<Arc<Limiters>>,
+    config: Arc<ServerConfig>,
+    stats: Arc<ServerStats>,
+) {
+    let mut stream = BufReader::new(stream);
+    let client = read_hello(&mut stream).await;
+
+    if let Some(limiters) = limiters {
+        let arrived = Instant::now();
+        let mut delayed = false;
+        while let Err(wait) = limiters.check(&client, Instant::now()) {
+            match limiters.on_limit() {
+                OnLimit::Delay { max } if arrived.elapsed() + wait <= max => {
+                    delayed = true;
+                    sleep(wait).await;
+                }
+                _ => {
+                    stats.rate_limited.fetch_add(1, Ordering::SeqCst);
+                    println!("Conexión {} de {} rechazada: límite de tasa.", id, client);
+                    reply_and_close(stream.into_inner(), RATE_LIMITED).await;
+                    return;
+                }
+            }
+        }
+        if delayed {
+            stats.delayed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let waiting = Instant::now();
+    let permit = match config.admission {
+        Admission::Refuse => match semaphore.try_acquire_owned() {
+            Ok(permit) => Permit::Semaphore { _permit: permit },
+            Err(_) => {
+                stats.refused.fetch_add(1, Ordering::SeqCst);
+                println!("Conexión {} de {} rechazada: servidor ocupado.", id, client);
+                reply_and_close(stream.into_inner(), BUSY).await;
+                return;
+            }
+        },
+        Admission::Queue => match Arc::clone(&semaphore).try_acquire_owned() {
+            Ok(permit) => Permit::Semaphore { _permit: permit },
+            Err(_) => {
+                stats.queued.fetch_add(1, Ordering::SeqCst);
+                Permit::Semaphore { _permit: semaphore.acquire_owned().await.unwrap() }
+            }
+        },
+        Admission::Fair => Permit::Fair { _permit: gate.acquire(&client).await },
+    };
+    stats.record_admission(&client, waiting.elapsed());
+    handle_connection(id, stream.into_inner(), permit, stats, config.session_time).await;
+}
+
+/// Lugar de una sesión admitida; se libera al soltarlo. Los permisos no se leen: solo se
+/// guardan hasta que termina la sesión.
+enum Permit {
+    Semaphore { _permit: OwnedSemaphorePermit },
+    Fair { _permit: FairPermit },
+}
+
+/// Lee el saludo del cliente.
 ///
-/// @Arc::clone() Clona el semáforo compartido para que cada tarea lo utilice.
-/// @semaphore.acquire() Adquiere un permiso del semáforo antes de manejar la conexión.
-/// @sleep(Duration::from_secs(2)) Simula el tiempo necesario para procesar la conexión.
-/// @await Libera el permiso automáticamente al salir del alcance.
+/// # Retorno
+/// Retorna el identificador del cliente, o `anónimo` si no se presentó a tiempo.
+async fn read_hello(stream: &mut BufReader<TcpStream>) -> String {
+    let mut line = String::new();
+    let _ = timeout(HELLO_TIMEOUT, stream.read_line(&mut line)).await;
+    let mut words = line.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some(HELLO), Some(client)) => client.to_string(),
+        _ => "anónimo".to_string(),
+    }
+}
+
+/// Atiende la sesión de un cliente admitido.
 ///
 /// # Parámetros
-/// - `id`: Identificador único del usuario que realiza la conexión.
-/// - `semaphore`: Semáforo compartido para controlar el número de conexiones activas.
-async fn handle_connection(id: usize, semaphore: Arc<Semaphore>) {
-    let _permit = semaphore.acquire().await.unwrap();
+/// - `id`: número de la conexión, en orden de llegada.
+/// - `stream`: socket del cliente.
+/// - `_permit`: lugar de la sesión; se libera al terminar la sesión.
+/// - `stats`: contadores del servidor.
+/// - `session_time`: tiempo que se mantiene abierta la sesión.
+async fn handle_connection(
+    id: usize,
+    mut stream: TcpStream,
+    _permit: Permit,
+    stats: Arc<ServerStats>,
+    session_time: Duration,
+) {
+    let active = stats.session_started();
+    println!("Conexión {} aceptada. Sesiones activas: {}", id, active);
 
-    println!("Conexión {} aceptada. ", id);
-    
-    sleep(Duration::from_secs(2)).await;
+    // Si el cliente ya se fue, la sesión termina igual y libera su permiso.
+    if stream.write_all(format!("{} {}\n", WELCOME, id).as_bytes()).await.is_ok() {
+        sleep(session_time).await;
+        let _ = stream.shutdown().await;
+    }
 
+    stats.session_ended();
     println!("Conexión {} terminada.", id);
-}
\ No newline at end of file
+}
+
+/// Envía una respuesta de rechazo al cliente y cierra la conexión.
+async fn reply_and_close(mut stream: TcpStream, reply: &str) {
+    let _ = stream.write_all(format!("{}\n", reply).as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Levanta un servidor con `config`, le envía la carga de `plan` y lo detiene.
+///
+/// # Retorno
+/// Retorna el reporte de los clientes y los contadores del servidor.
+pub async fn run_scenario(config: ServerConfig, plan: &LoadPlan) -> io::Result<(LoadReport, Arc<ServerStats>)> {
+    let server = Server::bind(config).await?;
+    let addr = server.local_addr()?;
+    let stats = server.stats();
+    let server = task::spawn(server.run());
+    let report = generate_load(addr, plan).await;
+    server.abort();
+    Ok((report, stats))
+}
+
+/// Inicia el servidor con `config` y lo carga con `clients` clientes simultáneos.
+/// Al terminar los clientes imprime su reporte y detiene el servidor.
+pub async fn start_connection(config: ServerConfig, clients: usize) {
+    let port = config.port;
+    println!(
+        "Servidor en el puerto {} (máximo {} sesiones, modo {}).",
+        port, config.max_connections, config.admission
+    );
+    match run_scenario(config, &LoadPlan::single_burst(clients)).await {
+        Ok((report, stats)) => {
+            report.print();
+            println!(
+                "Servidor: {} sesiones atendidas, {} esperaron en cola, {} rechazadas, máximo {} simultáneas.",
+                stats.served(),
+                stats.queued(),
+                stats.refused(),
+                stats.peak()
+            );
+        }
+        Err(error) => println!("No se pudo abrir el puerto {}: {}", port, error),
+    }
+}
+
+/// Somete al servidor a la misma carga en ráfagas con distintas combinaciones de semáforo
+/// y limitador de tasa, e imprime una tabla comparativa.
+///
+/// El semáforo solo mira cuántas sesiones hay abiertas: con él solo, cada ráfaga entra
+/// entera en la cola o se rechaza lo que no cabe. El limitador mira cuántas llegan: reparte
+/// las ráfagas en el tiempo, o corta a los clientes que llegan demasiado seguido.
+pub async fn compare_rate_limits() {
+    let plan = LoadPlan { bursts: 4, burst_size: 12, interval: Duration::from_millis(500), client_ids: 4 };
+    let base = ServerConfig { port: 0, session_time: Duration::from_millis(100), ..ServerConfig::default() };
+    let bucket = Algorithm::TokenBucket { burst: 4, per_second: 8.0 };
+    let window = Algorithm::SlidingWindow { limit: 2, window: Duration::from_millis(500) };
+    let delay = OnLimit::Delay { max: Duration::from_secs(1) };
+    let limited = |algorithm, scope, on_limit| ServerConfig {
+        rate_limit: Some(RateLimitConfig { algorithm, scope, on_limit }),
+        ..base.clone()
+    };
+    let scenarios = [
+        ServerConfig { admission: Admission::Queue, ..base.clone() },
+        ServerConfig { admission: Admission::Refuse, ..base.clone() },
+        limited(bucket, Scope::Global, OnLimit::Reject),
+        limited(bucket, Scope::Global, delay),
+        limited(window, Scope::PerClient, OnLimit::Reject),
+        limited(window, Scope::PerClient, delay),
+    ];
+
+    println!(
+        "===== {} ráfagas de {} conexiones cada {}ms, {} clientes, {} sesiones de {}ms =====",
+        plan.bursts,
+        plan.burst_size,
+        plan.interval.as_millis(),
+        plan.client_ids,
+        base.max_connections,
+        base.session_time.as_millis()
+    );
+    println!(
+        "{:<58} {:>9} {:>9} {:>8} {:>8} {:>10} {:>10}",
+        "Configuración", "Admitidos", "Demorados", "Tasa", "Ocupado", "Lat. media", "Lat. máx"
+    );
+    for config in scenarios {
+        let label = match config.rate_limit {
+            Some(rate_limit) => format!("semáforo en {} + {}", config.admission, rate_limit),
+            None => format!("solo semáforo, {}", config.admission),
+        };
+        match run_scenario(config, &plan).await {
+            Ok((report, stats)) => println!(
+                "{:<58} {:>9} {:>9} {:>8} {:>8} {:>8.1}ms {:>8.1}ms",
+                label,
+                report.accepted(),
+                stats.delayed(),
+                stats.rate_limited(),
+                report.rejected,
+                report.mean_latency().as_secs_f64() * 1000.0,
+                report.max_latency().as_secs_f64() * 1000.0
+            ),
+            Err(error) => println!("{:<58} error: {}", label, error),
+        }
+    }
+}
+
+/// Clientes de la comparación de admisión: uno voraz que abre muchas conexiones de golpe
+/// y varios livianos que llegan justo después con pocas.
+const FAIRNESS_CLIENTS: [(&str, usize); 4] = [("voraz", 24), ("ana", 4), ("beto", 4), ("carla", 4)];
+/// Pesos de la comparación: `carla` vale por dos.
+const FAIRNESS_WEIGHTS: &[(&str, u32)] = &[("carla", 2)];
+
+/// Somete al servidor a un cliente voraz y varios livianos, primero con la cola del semáforo
+/// y después con admisión justa, e imprime la espera de cada cliente por un lugar.
+///
+/// Con el semáforo las conexiones entran en orden de llegada y los livianos esperan a que
+/// pase toda la ráfaga del voraz. Con admisión justa, mientras compiten, cada cliente recibe
+/// lugares en proporción a su peso y los livianos terminan enseguida.
+pub async fn compare_fairness() {
+    let base = ServerConfig {
+        port: 0,
+        max_connections: 2,
+        session_time: Duration::from_millis(50),
+        weights: FAIRNESS_WEIGHTS.iter().map(|&(client, weight)| (client.to_string(), weight)).collect(),
+        ..ServerConfig::default()
+    };
+    for admission in [Admission::Queue, Admission::Fair] {
+        let config = ServerConfig { admission, ..base.clone() };
+        println!("===== Admisión {}: {} sesiones de {}ms =====", admission, config.max_connections, config.session_time.as_millis());
+        let stats = match run_clients(config, &FAIRNESS_CLIENTS).await {
+            Ok((_, stats)) => stats,
+            Err(error) => {
+                println!("error: {}", error);
+                continue;
+            }
+        };
+        println!(
+            "{:<8} {:>5} {:>9} {:>12} {:>11} {:>12}",
+            "Cliente", "Peso", "Sesiones", "Espera media", "Espera máx", "Último turno"
+        );
+        let last_turns = stats.last_turns();
+        for (client, waits) in stats.client_waits() {
+            let weight = if admission == Admission::Fair { weight_of(&base.weights, &client) } else { 1 };
+            println!(
+                "{:<8} {:>5} {:>9} {:>10.1}ms {:>9.1}ms {:>12}",
+                client,
+                weight,
+                waits.count,
+                waits.mean().as_secs_f64() * 1000.0,
+                waits.max.as_secs_f64() * 1000.0,
+                last_turns[&client]
+            );
+        }
+    }
+}
+
+/// Levanta un servidor con `config`, conecta a cada cliente de `clients` con sus conexiones,
+/// uno detrás de otro, y lo detiene.
+async fn run_clients(config: ServerConfig, clients: &[(&str, usize)]) -> io::Result<(LoadReport, Arc<ServerStats>)> {
+    let server = Server::bind(config).await?;
+    let addr = server.local_addr()?;
+    let stats = server.stats();
+    let server = task::spawn(server.run());
+    let report = generate_clients(addr, clients, Duration::from_millis(20)).await;
+    server.abort();
+    Ok((report, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(admission: Admission) -> ServerConfig {
+        ServerConfig {
+            port: 0,
+            max_connections: 2,
+            admission,
+            session_time: Duration::from_millis(100),
+            rate_limit: None,
+            weights: HashMap::new(),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn queued_clients_are_all_served_within_the_limit() {
+        let (report, stats) = run_scenario(config(Admission::Queue), &LoadPlan::single_burst(8)).await.unwrap();
+        assert_eq!(report.accepted(), 8);
+        assert_eq!(report.rejected, 0);
+        assert_eq!(stats.peak(), 2);
+        // Las 2 primeras conexiones de la ráfaga toman los permisos y las demás esperan en cola.
+        assert_eq!(stats.queued(), 6);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn extra_clients_are_refused() {
+        let (report, stats) = run_scenario(config(Admission::Refuse), &LoadPlan::single_burst(8)).await.unwrap();
+        assert_eq!(report.accepted(), 2);
+        assert_eq!(report.rejected, 6);
+        assert_eq!(stats.refused(), 6);
+        assert!(stats.peak() <= 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn rate_limit_applies_before_the_semaphore() {
+        let config = ServerConfig {
+            max_connections: 10,
+            rate_limit: Some(RateLimitConfig {
+                algorithm: Algorithm::SlidingWindow { limit: 1, window: Duration::from_secs(10) },
+                scope: Scope::PerClient,
+                on_limit: OnLimit::Reject,
+            }),
+            ..config(Admission::Refuse)
+        };
+        // 3 clientes distintos con 2 conexiones cada uno: entra la primera de cada cliente.
+        let plan = LoadPlan { bursts: 1, burst_size: 6, interval: Duration::ZERO, client_ids: 3 };
+        let (report, stats) = run_scenario(config, &plan).await.unwrap();
+        assert_eq!(report.accepted(), 3);
+        assert_eq!(report.rate_limited, 3);
+        assert_eq!(stats.rate_limited(), 3);
+        assert_eq!(report.rejected, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn delayed_clients_are_admitted_later() {
+        let config = ServerConfig {
+            max_connections: 10,
+            rate_limit: Some(RateLimitConfig {
+                algorithm: Algorithm::TokenBucket { burst: 2, per_second: 20.0 },
+                scope: Scope::Global,
+                on_limit: OnLimit::Delay { max: Duration::from_secs(5) },
+            }),
+            ..config(Admission::Refuse)
+        };
+        let (report, stats) = run_scenario(config, &LoadPlan::single_burst(6)).await.unwrap();
+        assert_eq!(report.accepted(), 6);
+        // Las 4 que no caben en la ráfaga se demoran hasta que haya fichas; ninguna se rechaza.
+        assert_eq!(stats.delayed(), 4);
+        assert_eq!(stats.rate_limited(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn fair_admission_does_not_let_a_greedy_client_starve_the_others() {
+        let clients = [("voraz", 16), ("a", 2), ("b", 2), ("c", 2)];
+        let (report, stats) = run_clients(config(Admission::Fair), &clients).await.unwrap();
+        assert_eq!(report.accepted(), 22);
+        let (waits, last_turns) = (stats.client_waits(), stats.last_turns());
+        // El voraz ocupa los 2 lugares libres y, desde ahí, los turnos alternan entre los
+        // cuatro clientes: los livianos terminan en las 2 rondas siguientes, en los 10
+        // primeros turnos, y el resto de la ráfaga del voraz entra después.
+        for light in ["a", "b", "c"] {
+            assert_eq!(waits[light].count, 2);
+            assert!(last_turns[light] <= 10, "{}: turno {}", light, last_turns[light]);
+        }
+        assert_eq!(last_turns["voraz"], 22);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn the_semaphore_queue_serves_in_arrival_order() {
+        let clients = [("voraz", 16), ("a", 2), ("b", 2), ("c", 2)];
+        let (_, stats) = run_clients(config(Admission::Queue), &clients).await.unwrap();
+        let (waits, last_turns) = (stats.client_waits(), stats.last_turns());
+        // Sin admisión justa los livianos entran recién cuando pasa toda la ráfaga del voraz.
+        assert_eq!(waits["voraz"].count, 16);
+        assert_eq!(last_turns["voraz"], 16);
+    }
+}
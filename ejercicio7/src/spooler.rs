@@ -0,0 +1,518 @@
+/*
+Cola de impresión (spooler) para una flota de impresoras distintas. Los usuarios envían
+trabajos con sus requisitos (color, doble cara, tamaño de papel) y el spooler los reparte;
+cada impresora corre en su propio hilo y todo el estado compartido vive en un mutex con
+una variable de condición que se avisa en cada cambio.
+
+- Cola: los trabajos esperan en orden de llegada. `submit` rechaza enseguida un trabajo que
+  ninguna impresora de la flota puede imprimir, así nunca queda en la cola para siempre.
+- Reparto: cada vez que llega un trabajo o se libera una impresora, `dispatch` recorre la
+  cola en orden y asigna cada trabajo a la impresora libre más rápida que lo puede
+  imprimir. Un trabajo que solo cabe en una impresora ocupada espera sin frenar a los que
+  vienen detrás.
+- Cancelación: un trabajo en cola se quita de ella. Uno que se está imprimiendo se
+  interrumpe entre dos páginas: la impresora espera cada página en la variable de
+  condición en lugar de dormir, así que despierta en cuanto se cancela su trabajo y lo
+  deja con las páginas que llegó a imprimir.
+*/
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Tamaño de papel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaperSize {
+    A4,
+    A3,
+    Letter,
+}
+
+/// Lo que un trabajo necesita de la impresora.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Requirements {
+    pub color: bool,      // Imprime en color
+    pub duplex: bool,     // Imprime a doble cara
+    pub paper: PaperSize, // Tamaño de papel
+}
+
+impl Default for Requirements {
+    /// Blanco y negro, a una cara, en A4.
+    fn default() -> Self {
+        Self { color: false, duplex: false, paper: PaperSize::A4 }
+    }
+}
+
+impl fmt::Display for Requirements {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}, {}, {:?}",
+            if self.color { "color" } else { "B/N" },
+            if self.duplex { "doble cara" } else { "una cara" },
+            self.paper
+        )
+    }
+}
+
+/// Impresora de la oficina, con sus capacidades y su velocidad.
+#[derive(Clone, Debug)]
+pub struct Printer {
+    pub name: &'static str,     // Nombre que aparece en los mensajes
+    pub color: bool,            // Puede imprimir en color
+    pub duplex: bool,           // Puede imprimir a doble cara
+    pub papers: Vec<PaperSize>, // Tamaños de papel que acepta
+    pub page_time: Duration,    // Tiempo que tarda cada página
+}
+
+impl Printer {
+    /// Indica si la impresora cumple todo lo que pide `requirements`.
+    pub fn can_print(&self, requirements: &Requirements) -> bool {
+        (self.color || !requirements.color)
+            && (self.duplex || !requirements.duplex)
+            && self.papers.contains(&requirements.paper)
+    }
+}
+
+/// Impresoras de la oficina que usa la simulación del menú.
+pub fn office_fleet() -> Vec<Printer> {
+    vec![
+        Printer {
+            name: "Láser B/N",
+            color: false,
+            duplex: true,
+            papers: vec![PaperSize::A4, PaperSize::Letter],
+            page_time: Duration::from_millis(100),
+        },
+        Printer {
+            name: "Inyección color",
+            color: true,
+            duplex: false,
+            papers: vec![PaperSize::A4],
+            page_time: Duration::from_millis(300),
+        },
+        Printer {
+            name: "Plotter A3",
+            color: true,
+            duplex: true,
+            papers: vec![PaperSize::A4, PaperSize::A3],
+            page_time: Duration::from_millis(400),
+        },
+    ]
+}
+
+/// Estado de un trabajo de impresión.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobState {
+    Queued,                                      // Esperando una impresora libre
+    Printing { printer: usize, printed: usize }, // En una impresora, con las páginas ya impresas
+    Done,                                        // Impreso completo
+    Cancelled { printed: usize },                // Cancelado, con las páginas que llegaron a imprimirse
+}
+
+/// Trabajo de impresión enviado por un usuario.
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub id: usize,                  // Identificador asignado por el spooler
+    pub owner: String,              // Usuario que envió el trabajo
+    pub pages: usize,               // Páginas a imprimir
+    pub requirements: Requirements, // Lo que necesita de la impresora
+    pub state: JobState,            // Estado actual
+}
+
+/// Error de `submit`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SubmitError {
+    NoCompatiblePrinter, // Ninguna impresora cumple los requisitos del trabajo
+}
+
+/// Error de `cancel`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CancelError {
+    UnknownJob, // No hay un trabajo con ese identificador
+    Finished,   // El trabajo ya terminó o ya se había cancelado
+}
+
+/// Trabajos del spooler agrupados por estado, como los devuelve `status`.
+#[derive(Debug, Default)]
+pub struct SpoolerStatus {
+    pub queued: Vec<Job>,   // En la cola, en orden de llegada
+    pub printing: Vec<Job>, // En alguna impresora
+    pub finished: Vec<Job>, // Impresos o cancelados
+}
+
+impl SpoolerStatus {
+    /// Imprime los trabajos de cada grupo.
+    pub fn print(&self) {
+        println!("--- Estado del spooler ---");
+        for (label, jobs) in [("En cola", &self.queued), ("Imprimiendo", &self.printing), ("Terminados", &self.finished)] {
+            println!("{} ({}):", label, jobs.len());
+            for job in jobs {
+                println!(
+                    "  #{} de {}, {} páginas ({}): {:?}",
+                    job.id, job.owner, job.pages, job.requirements, job.state
+                );
+            }
+        }
+    }
+}
+
+/// Estado interno del spooler, protegido por su mutex.
+struct SpoolerState {
+    jobs: BTreeMap<usize, Job>,   // Todos los trabajos enviados, por identificador
+    queue: VecDeque<usize>,       // Trabajos en espera, en orden de llegada
+    assigned: Vec<Option<usize>>, // Trabajo asignado a cada impresora, por posición
+    next_id: usize,               // Identificador del próximo trabajo
+    shutdown: bool,               // Si las impresoras deben terminar al vaciar la cola
+}
+
+/// Cola de impresión atendida por un grupo de impresoras distintas, cada una en su propio hilo.
+pub struct Spooler {
+    state: Mutex<SpoolerState>,
+    changed: Condvar, // Se avisa en cada cambio de la cola o del estado de un trabajo
+    printers: Vec<Printer>,
+    threads: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl Spooler {
+    /// Crea el spooler y arranca sus impresoras.
+    ///
+    /// # Parámetros
+    /// - `printers`: impresoras disponibles. Se numeran desde 1 en el orden dado.
+    ///
+    /// # Retorno
+    /// Retorna el spooler compartido con los hilos de las impresoras.
+    pub fn start(printers: Vec<Printer>) -> Arc<Self> {
+        let spooler = Arc::new(Spooler {
+            state: Mutex::new(SpoolerState {
+                jobs: BTreeMap::new(),
+                queue: VecDeque::new(),
+                assigned: vec![None; printers.len()],
+                next_id: 1,
+                shutdown: false,
+            }),
+            changed: Condvar::new(),
+            printers,
+            threads: Mutex::new(Vec::new()),
+        });
+
+        let handles = (1..=spooler.printers.len())
+            .map(|printer| {
+                let spooler = Arc::clone(&spooler);
+                thread::spawn(move || spooler.run_printer(printer))
+            })
+            .collect();
+        *spooler.threads.lock().unwrap() = handles;
+        spooler
+    }
+
+    /// Agrega un trabajo al final de la cola.
+    ///
+    /// # Retorno
+    /// Retorna el identificador del trabajo, o `NoCompatiblePrinter` si ninguna impresora
+    /// puede imprimirlo.
+    pub fn submit(&self, owner: &str, pages: usize, requirements: Requirements) -> Result<usize, SubmitError> {
+        if !self.printers.iter().any(|printer| printer.can_print(&requirements)) {
+            println!("Trabajo de {} rechazado: ninguna impresora admite {}.", owner, requirements);
+            return Err(SubmitError::NoCompatiblePrinter);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.jobs.insert(
+            id,
+            Job { id, owner: owner.to_string(), pages, requirements, state: JobState::Queued },
+        );
+        state.queue.push_back(id);
+        println!("Trabajo #{} de {} ({} páginas, {}) en cola.", id, owner, pages, requirements);
+        self.dispatch(&mut state);
+        self.changed.notify_all();
+        Ok(id)
+    }
+
+    /// Cancela un trabajo en cola o en impresión.
+    ///
+    /// # Retorno
+    /// Retorna el estado del trabajo antes de cancelarlo.
+    pub fn cancel(&self, id: usize) -> Result<JobState, CancelError> {
+        let mut state = self.state.lock().unwrap();
+        let job = state.jobs.get_mut(&id).ok_or(CancelError::UnknownJob)?;
+        let previous = job.state;
+        job.state = match previous {
+            JobState::Queued => JobState::Cancelled { printed: 0 },
+            JobState::Printing { printed, .. } => JobState::Cancelled { printed },
+            JobState::Done | JobState::Cancelled { .. } => return Err(CancelError::Finished),
+        };
+        if previous == JobState::Queued {
+            state.queue.retain(|&queued| queued != id);
+        }
+        println!("Trabajo #{} cancelado.", id);
+        self.changed.notify_all();
+        Ok(previous)
+    }
+
+    /// Retorna una copia de los trabajos agrupados por estado.
+    pub fn status(&self) -> SpoolerStatus {
+        let state = self.state.lock().unwrap();
+        let mut status = SpoolerStatus::default();
+        for &id in &state.queue {
+            status.queued.push(state.jobs[&id].clone());
+        }
+        for job in state.jobs.values() {
+            match job.state {
+                JobState::Queued => {}
+                JobState::Printing { .. } => status.printing.push(job.clone()),
+                JobState::Done | JobState::Cancelled { .. } => status.finished.push(job.clone()),
+            }
+        }
+        status
+    }
+
+    /// Espera a que la cola se vacíe y ninguna impresora esté imprimiendo.
+    pub fn wait_idle(&self) {
+        let state = self.state.lock().unwrap();
+        let _idle = self
+            .changed
+            .wait_while(state, |state| {
+                !state.queue.is_empty() || state.assigned.iter().any(Option::is_some)
+            })
+            .unwrap();
+    }
+
+    /// Deja que las impresoras terminen los trabajos pendientes y espera a sus hilos.
+    pub fn shutdown(&self) {
+        self.state.lock().unwrap().shutdown = true;
+        self.changed.notify_all();
+        for handle in self.threads.lock().unwrap().drain(..) {
+            handle.join().unwrap();
+        }
+    }
+
+    /// Asigna los trabajos en cola, en orden de llegada, a la impresora libre más rápida
+    /// que puede imprimir cada uno.
+    fn dispatch(&self, state: &mut SpoolerState) {
+        let mut waiting = VecDeque::new();
+        while let Some(id) = state.queue.pop_front() {
+            let requirements = state.jobs[&id].requirements;
+            let fastest = (0..self.printers.len())
+                .filter(|&index| state.assigned[index].is_none() && self.printers[index].can_print(&requirements))
+                .min_by_key(|&index| self.printers[index].page_time);
+            match fastest {
+                Some(index) => {
+                    state.assigned[index] = Some(id);
+                    state.jobs.get_mut(&id).unwrap().state = JobState::Printing { printer: index + 1, printed: 0 };
+                }
+                None => waiting.push_back(id),
+            }
+        }
+        state.queue = waiting;
+    }
+
+    /// Bucle de una impresora: imprime los trabajos que le asigna el spooler hasta que este
+    /// se cierra y la cola está vacía.
+    fn run_printer(&self, printer: usize) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let id = loop {
+                if let Some(id) = state.assigned[printer - 1] {
+                    break id;
+                }
+                if state.shutdown && state.queue.is_empty() {
+                    return;
+                }
+                state = self.changed.wait(state).unwrap();
+            };
+            state = self.print_job(state, printer, id);
+            state.assigned[printer - 1] = None;
+            self.dispatch(&mut state);
+            self.changed.notify_all();
+        }
+    }
+
+    /// Imprime un trabajo página por página, sin retener el estado mientras espera.
+    ///
+    /// # Retorno
+    /// Retorna el estado del spooler bloqueado, con el trabajo terminado o cancelado.
+    fn print_job<'a>(&'a self, mut state: MutexGuard<'a, SpoolerState>, printer: usize, id: usize) -> MutexGuard<'a, SpoolerState> {
+        let device = &self.printers[printer - 1];
+        let job = &state.jobs[&id];
+        let pages = job.pages;
+        println!(
+            "Impresora {} ({}) empieza el trabajo #{} de {} ({} páginas).",
+            printer, device.name, id, job.owner, pages
+        );
+
+        for page in 1..=pages {
+            let deadline = Instant::now() + device.page_time;
+            loop {
+                if matches!(state.jobs[&id].state, JobState::Cancelled { .. }) {
+                    println!("Impresora {} deja el trabajo #{} en la página {}.", printer, id, page);
+                    return state;
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                state = self.changed.wait_timeout(state, deadline - now).unwrap().0;
+            }
+            state.jobs.get_mut(&id).unwrap().state = JobState::Printing { printer, printed: page };
+        }
+
+        state.jobs.get_mut(&id).unwrap().state = JobState::Done;
+        println!("Impresora {} terminó el trabajo #{}.", printer, id);
+        state
+    }
+}
+
+/// Simula una oficina que envía trabajos con distintos requisitos a su flota de impresoras,
+/// cancela uno en cola y otro a medio imprimir, y consulta el estado mientras imprimen.
+pub fn run_spooler() {
+    let fleet = office_fleet();
+    for (index, printer) in fleet.iter().enumerate() {
+        println!(
+            "Impresora {}: {} ({}, {}, papel {:?}, {} ms por página)",
+            index + 1,
+            printer.name,
+            if printer.color { "color" } else { "B/N" },
+            if printer.duplex { "doble cara" } else { "una cara" },
+            printer.papers,
+            printer.page_time.as_millis()
+        );
+    }
+    let spooler = Spooler::start(fleet);
+
+    let color = Requirements { color: true, ..Requirements::default() };
+    let duplex = Requirements { duplex: true, ..Requirements::default() };
+    let poster = Requirements { color: true, paper: PaperSize::A3, ..Requirements::default() };
+    let impossible = Requirements { color: true, duplex: true, paper: PaperSize::Letter };
+    let submissions = [
+        ("ana", 3, Requirements::default()),
+        ("beto", 6, color),
+        ("carla", 2, duplex),
+        ("dario", 4, poster),
+        ("eva", 1, impossible),
+        ("fabio", 2, color),
+        ("gina", 5, Requirements::default()),
+    ];
+    let jobs: Vec<(&str, usize)> = submissions
+        .iter()
+        .filter_map(|&(owner, pages, requirements)| Some((owner, spooler.submit(owner, pages, requirements).ok()?)))
+        .collect();
+
+    thread::sleep(Duration::from_millis(750));
+    spooler.status().print();
+
+    // El último trabajo de color sigue en cola; el primero ya va por la mitad.
+    for owner in ["fabio", "beto"] {
+        let Some(&(_, id)) = jobs.iter().find(|&&(name, _)| name == owner) else {
+            println!("{} no tiene trabajos que cancelar.", owner);
+            continue;
+        };
+        if let Err(error) = spooler.cancel(id) {
+            println!("No se pudo cancelar el trabajo #{}: {:?}", id, error);
+        }
+    }
+
+    spooler.wait_idle();
+    spooler.status().print();
+    spooler.shutdown();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: Duration = Duration::from_millis(20);
+
+    fn printer(name: &'static str, color: bool, page_time: Duration) -> Printer {
+        Printer { name, color, duplex: false, papers: vec![PaperSize::A4], page_time }
+    }
+
+    fn identical(printers: usize) -> Vec<Printer> {
+        (0..printers).map(|_| printer("igual", false, PAGE)).collect()
+    }
+
+    #[test]
+    fn prints_every_job_and_reports_them_done() {
+        let spooler = Spooler::start(identical(2));
+        for pages in [1, 2, 3, 1] {
+            spooler.submit("ana", pages, Requirements::default()).unwrap();
+        }
+        spooler.wait_idle();
+        let status = spooler.status();
+        assert!(status.queued.is_empty() && status.printing.is_empty());
+        assert!(status.finished.iter().all(|job| job.state == JobState::Done));
+        assert_eq!(status.finished.len(), 4);
+        spooler.shutdown();
+    }
+
+    #[test]
+    fn cancels_queued_and_printing_jobs() {
+        let spooler = Spooler::start(identical(1));
+        let printing = spooler.submit("ana", 50, Requirements::default()).unwrap();
+        let queued = spooler.submit("beto", 1, Requirements::default()).unwrap();
+        thread::sleep(PAGE * 3);
+
+        assert_eq!(spooler.cancel(queued), Ok(JobState::Queued));
+        assert!(matches!(spooler.cancel(printing), Ok(JobState::Printing { printer: 1, .. })));
+        assert_eq!(spooler.cancel(printing), Err(CancelError::Finished));
+        assert_eq!(spooler.cancel(99), Err(CancelError::UnknownJob));
+
+        spooler.wait_idle();
+        let status = spooler.status();
+        assert_eq!(status.finished[1].state, JobState::Cancelled { printed: 0 });
+        assert!(matches!(status.finished[0].state, JobState::Cancelled { printed } if printed < 50));
+        spooler.shutdown();
+    }
+
+    #[test]
+    fn assigns_the_fastest_compatible_free_printer() {
+        let spooler = Spooler::start(vec![
+            printer("lenta color", true, PAGE * 4),
+            printer("rápida color", true, PAGE),
+            printer("rápida B/N", false, PAGE / 2),
+        ]);
+        let color = Requirements { color: true, ..Requirements::default() };
+        let first = spooler.submit("ana", 20, color).unwrap();
+        let second = spooler.submit("beto", 20, color).unwrap();
+        let mono = spooler.submit("carla", 20, Requirements::default()).unwrap();
+
+        let status = spooler.status();
+        let printer_of = |id: usize| match status.printing.iter().find(|job| job.id == id).unwrap().state {
+            JobState::Printing { printer, .. } => printer,
+            state => panic!("el trabajo #{} no está imprimiendo: {:?}", id, state),
+        };
+        assert_eq!((printer_of(first), printer_of(second), printer_of(mono)), (2, 1, 3));
+
+        for id in [first, second, mono] {
+            spooler.cancel(id).unwrap();
+        }
+        spooler.shutdown();
+    }
+
+    #[test]
+    fn a_job_waits_for_its_only_compatible_printer_without_blocking_others() {
+        let spooler = Spooler::start(vec![printer("color", true, PAGE), printer("B/N", false, PAGE)]);
+        let color = Requirements { color: true, ..Requirements::default() };
+        spooler.submit("ana", 20, color).unwrap();
+        let waiting = spooler.submit("beto", 1, color).unwrap();
+        let mono = spooler.submit("carla", 1, Requirements::default()).unwrap();
+
+        let status = spooler.status();
+        assert_eq!(status.queued.iter().map(|job| job.id).collect::<Vec<_>>(), vec![waiting]);
+        assert!(status.printing.iter().any(|job| job.id == mono));
+        spooler.wait_idle();
+        spooler.shutdown();
+    }
+
+    #[test]
+    fn rejects_jobs_no_printer_can_satisfy() {
+        let spooler = Spooler::start(identical(2));
+        let a3 = Requirements { paper: PaperSize::A3, ..Requirements::default() };
+        assert_eq!(spooler.submit("ana", 1, a3), Err(SubmitError::NoCompatiblePrinter));
+        assert!(spooler.status().queued.is_empty());
+        spooler.shutdown();
+    }
+}
@@ -1,7 +1,12 @@
-use std::sync::{Arc, Mutex};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap, HashSet};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
-use std::time::Duration;
-use tokio::sync::Semaphore;
+use std::time::{Duration, Instant};
+use crate::wait_stats::WaitStats;
+
+/// Cada cuánto tiempo de espera sube en 1 la prioridad efectiva de un usuario.
+pub const AGING_STEP: Duration = Duration::from_millis(500);
 
 // Permite duplicar el valor en lugar de moverlo
 #[derive(Clone)]
@@ -10,9 +15,63 @@ struct User {
     priority: usize,
 }
 
+/// Usuario esperando una impresora.
+///
+/// Con envejecimiento, la prioridad efectiva es `priority + esperado / aging_step`. Entre dos
+/// usuarios, en cualquier instante, gana el de menor `llegada - priority * aging_step`, así que
+/// esa clave no cambia mientras esperan y alcanza con un `BinaryHeap`.
+struct Waiter {
+    key: i128,          // Llegada en ns desde el inicio del gestor, menos la prioridad en ns de espera
+    ticket: u64,        // Orden de llegada, para desempatar y para entregar la impresora
+    enqueued: Instant,  // Instante de llegada a la cola
+    priority: usize,    // Prioridad con la que llegó
+    wake: Arc<Condvar>, // Solo este usuario espera en esta variable de condición
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.key, self.ticket).cmp(&(other.key, other.ticket))
+    }
+}
+
+/// Estado del gestor, protegido por su mutex.
+struct ManagerState {
+    free: usize,                        // Impresoras sin usuario
+    queue: BinaryHeap<Reverse<Waiter>>, // Usuarios en espera; el primero es el de mayor prioridad efectiva
+    granted: HashSet<u64>,              // Turnos a los que ya se entregó una impresora
+    next_ticket: u64,                   // Turno del próximo usuario que llega
+    waits: BTreeMap<usize, WaitStats>,  // Esperas por prioridad original
+}
+
 struct PrinterManager {
-    semaphore: Arc<Semaphore>,
-    priority_queue: Arc<Mutex<Vec<User>>>,
+    state: Mutex<ManagerState>,
+    aging_step: Duration, // Espera que equivale a un nivel de prioridad
+    epoch: Instant,       // Origen de las claves de la cola
+}
+
+/// Impresora asignada a un usuario. Al soltarla pasa al siguiente usuario de la cola.
+struct PrinterGrant<'a> {
+    manager: &'a PrinterManager,
+}
+
+impl Drop for PrinterGrant<'_> {
+    fn drop(&mut self) {
+        self.manager.release();
+    }
 }
 
 impl PrinterManager {
@@ -20,116 +79,219 @@ impl PrinterManager {
     ///
     /// # Parámetros
     /// - `max_printers`: Número máximo de impresoras disponibles.
+    /// - `aging_step`: Espera tras la cual la prioridad efectiva de un usuario sube en 1.
     ///
     /// # Retorno
-    /// Retorna una instancia de `PrinterManager` con un semáforo inicializado y una cola de prioridad vacía.
-    ///
-    /// @Arc::new(Semaphore::new()) Inicializa el semáforo con el número máximo de permisos.
-    /// @Mutex<Vec<User>> Protege la cola de prioridad para acceso seguro entre hilos.
-    fn new(max_printers: usize) -> Self {
+    /// Retorna una instancia de `PrinterManager` con todas las impresoras libres y la cola vacía.
+    fn new(max_printers: usize, aging_step: Duration) -> Self {
         Self {
-            semaphore: Arc::new(Semaphore::new(max_printers)),
-            priority_queue: Arc::new(Mutex::new(Vec::new())),
+            state: Mutex::new(ManagerState {
+                free: max_printers,
+                queue: BinaryHeap::new(),
+                granted: HashSet::new(),
+                next_ticket: 0,
+                waits: BTreeMap::new(),
+            }),
+            aging_step,
+            epoch: Instant::now(),
         }
     }
 
-    /// Agrega un usuario a la cola de prioridad.
+    /// Espera hasta obtener una impresora.
     ///
-    /// Este método inserta un usuario en la cola de prioridad y reorganiza la cola
-    /// para que los usuarios con mayor prioridad aparezcan al principio.
+    /// Si hay una libre la toma enseguida. Si no, entra en la cola y duerme en su propia
+    /// variable de condición hasta que quien libere una impresora se la entregue: no hay
+    /// sondeo, y nadie más puede quitársela entre el aviso y el despertar.
     ///
     /// # Parámetros
-    /// - `user`: Instancia del usuario a agregar.
+    /// - `user`: Usuario que pide la impresora.
     ///
-    /// @priority_queue.push(user) Inserta al usuario en la cola.
-    /// @priority_queue.sort_by() Ordena la cola en orden descendente de prioridades.
-    fn add_user(&self, user: User) {
-        let mut queue = self.priority_queue.lock().unwrap();
-        queue.push(user);
-        queue.sort_by(|a, b| b.priority.cmp(&a.priority));
+    /// # Retorno
+    /// Retorna la impresora asignada; se libera al soltar el valor.
+    fn acquire(&self, user: &User) -> PrinterGrant<'_> {
+        let mut state = self.state.lock().unwrap();
+        let enqueued = Instant::now();
+        if state.free > 0 && state.queue.is_empty() {
+            state.free -= 1;
+            Self::record_wait(&mut state, user.priority, Duration::ZERO);
+            return PrinterGrant { manager: self };
+        }
+
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        let wake = Arc::new(Condvar::new());
+        let arrival = enqueued.duration_since(self.epoch).as_nanos() as i128;
+        state.queue.push(Reverse(Waiter {
+            key: arrival - user.priority as i128 * self.aging_step.as_nanos() as i128,
+            ticket,
+            enqueued,
+            priority: user.priority,
+            wake: Arc::clone(&wake),
+        }));
+
+        while !state.granted.remove(&ticket) {
+            state = wake.wait(state).unwrap();
+        }
+        PrinterGrant { manager: self }
+    }
+
+    /// Libera una impresora y, si hay usuarios esperando, se la entrega al de mayor prioridad efectiva.
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        match state.queue.pop() {
+            Some(Reverse(waiter)) => {
+                let waited = waiter.enqueued.elapsed();
+                println!(
+                    "Impresora entregada al turno {} (prioridad {}, efectiva {} tras esperar {:.1}s).",
+                    waiter.ticket,
+                    waiter.priority,
+                    self.effective_priority(waiter.priority, waited),
+                    waited.as_secs_f64()
+                );
+                Self::record_wait(&mut state, waiter.priority, waited);
+                state.granted.insert(waiter.ticket);
+                waiter.wake.notify_one();
+            }
+            None => state.free += 1,
+        }
+    }
+
+    /// Prioridad de un usuario después de esperar `waited`.
+    fn effective_priority(&self, priority: usize, waited: Duration) -> usize {
+        priority + (waited.as_nanos() / self.aging_step.as_nanos().max(1)) as usize
+    }
+
+    fn record_wait(state: &mut ManagerState, priority: usize, waited: Duration) {
+        state.waits.entry(priority).or_default().record(waited);
+    }
+
+    /// Retorna los tiempos de espera por prioridad.
+    fn wait_stats(&self) -> BTreeMap<usize, WaitStats> {
+        self.state.lock().unwrap().waits.clone()
     }
 
     /// Gestiona el acceso de un usuario a una impresora.
     ///
-    /// Este método permite que un usuario acceda a una impresora si:
-    /// 1. Tiene una de las dos prioridades más altas en la cola.
-    /// 2. Hay una impresora disponible.
-    ///
     /// # Parámetros
     /// - `user`: Instancia del usuario que intenta acceder.
-    ///
-    /// @priority_queue.lock() Protege la cola de prioridad para verificar la posición del usuario.
-    /// @semaphore.try_acquire() Adquiere un permiso del semáforo si hay impresoras disponibles.
-    /// @thread::sleep() Simula el tiempo que el usuario utiliza la impresora.
-    fn manage_access(&self, user: User) {
-        loop {
-            let permission_available = {
-                let queue = self.priority_queue.lock().unwrap();
-                if let Some(pos) = queue.iter().position(|u| u.id == user.id) {
-                    pos < 2
-                } else {
-                    false
-                }
-            };
-
-            if permission_available {
-                if let Ok(_permit) = self.semaphore.try_acquire() {
-                    println!(
-                        "Usuario {} (prioridad {}) ha adquirido impresora.",
-                        user.id, user.priority
-                    );
-
-                    thread::sleep(Duration::from_secs(2));
-
-                    println!("Usuario {} ha liberado impresora.", user.id);
-
-                    let mut queue = self.priority_queue.lock().unwrap();
-                    if let Some(pos) = queue.iter().position(|u| u.id == user.id) {
-                        queue.remove(pos);
-                    }
-                    break;
-                }
-            } else {
-                thread::sleep(Duration::from_millis(100));
-            }
-        }
+    /// - `printing_time`: Tiempo que el usuario utiliza la impresora.
+    fn manage_access(&self, user: User, printing_time: Duration) {
+        let _printer = self.acquire(&user);
+        println!("Usuario {} (prioridad {}) ha adquirido impresora.", user.id, user.priority);
+
+        thread::sleep(printing_time);
+
+        println!("Usuario {} ha liberado impresora.", user.id);
     }
 }
 
 /// Simula el acceso de múltiples usuarios a impresoras compartidas con manejo de prioridades.
 ///
-/// La función crea usuarios con prioridades asignadas y utiliza múltiples hilos
-/// para simular su acceso a impresoras compartidas. El acceso es gestionado por `PrinterManager`,
-/// que asegura que los usuarios con mayor prioridad tengan acceso preferencial.
+/// Además de los usuarios iniciales llegan usuarios de prioridad alta mientras dura la
+/// simulación. Sin envejecimiento los de prioridad baja esperarían a que dejen de llegar;
+/// con él, su prioridad efectiva crece mientras esperan hasta que les toca.
 ///
 /// @thread::spawn() Crea un hilo por usuario.
-/// @PrinterManager.add_user() Agrega al usuario a la cola de prioridad.
 /// @PrinterManager.manage_access() Gestiona el acceso del usuario a la impresora.
 pub fn use_printer_with_priority() {
     let max_connection = 2;
     let total_user = 10;
+    let late_users = 10;
+    let printing_time = Duration::from_secs(1);
+
+    let manager = Arc::new(PrinterManager::new(max_connection, AGING_STEP));
 
-    let manager = Arc::new(PrinterManager::new(max_connection));
+    let mut handles = vec![];
+    let mut spawn_user = |user: User| {
+        let manager_clone = Arc::clone(&manager);
+        handles.push(thread::spawn(move || {
+            manager_clone.manage_access(user, printing_time);
+        }));
+    };
 
-    let mut users = Vec::new();
     for id in 1..=total_user {
-        users.push(User {
+        spawn_user(User {
             id,
             priority: 10 - id + 1,
         });
     }
-    
-    let mut handles = vec![];
-
-    for user in users {
-        let manager_clone = Arc::clone(&manager);
-        handles.push(thread::spawn(move || {
-            manager_clone.add_user(user.clone());
-            manager_clone.manage_access(user);
-        }));
+    for id in total_user + 1..=total_user + late_users {
+        thread::sleep(printing_time / 2);
+        spawn_user(User { id, priority: 10 });
     }
 
     for handle in handles {
         handle.join().unwrap();
     }
+
+    println!("{:>9} {:>8} {:>12} {:>11}", "Prioridad", "Usuarios", "Espera media", "Espera máx");
+    for (priority, stats) in manager.wait_stats().iter().rev() {
+        println!(
+            "{:>9} {:>8} {:>11.2}s {:>10.2}s",
+            priority,
+            stats.count,
+            stats.mean().as_secs_f64(),
+            stats.max.as_secs_f64()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(20);
+
+    /// Ocupa la única impresora, hace llegar a `first` y, `gap` después, a `second`.
+    /// Retorna los identificadores en el orden en que obtuvieron la impresora.
+    fn grant_order(first: User, gap: Duration, second: User) -> Vec<usize> {
+        let manager = Arc::new(PrinterManager::new(1, STEP));
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let holder = manager.acquire(&User { id: 0, priority: 0 });
+
+        let mut handles = vec![];
+        for (delay, user) in [(Duration::ZERO, first), (gap, second)] {
+            thread::sleep(delay);
+            let manager = Arc::clone(&manager);
+            let order = Arc::clone(&order);
+            handles.push(thread::spawn(move || {
+                let _printer = manager.acquire(&user);
+                order.lock().unwrap().push(user.id);
+            }));
+        }
+        // Esperar a que los dos estén en la cola.
+        while manager.state.lock().unwrap().queue.len() < 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        drop(holder);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[test]
+    fn higher_priority_goes_first() {
+        let order = grant_order(User { id: 1, priority: 1 }, Duration::ZERO, User { id: 2, priority: 5 });
+        assert_eq!(order, vec![2, 1]);
+    }
+
+    #[test]
+    fn aging_lets_a_long_wait_overtake_a_higher_priority() {
+        // Tras 10 pasos de espera, prioridad 1 vale más que una prioridad 5 recién llegada.
+        let order = grant_order(User { id: 1, priority: 1 }, STEP * 10, User { id: 2, priority: 5 });
+        assert_eq!(order, vec![1, 2]);
+    }
+
+    #[test]
+    fn wait_stats_are_grouped_by_priority() {
+        let manager = PrinterManager::new(1, STEP);
+        drop(manager.acquire(&User { id: 1, priority: 3 }));
+        drop(manager.acquire(&User { id: 2, priority: 3 }));
+        let stats = manager.wait_stats();
+        assert_eq!(stats[&3].count, 2);
+        assert_eq!(manager.state.lock().unwrap().free, 1);
+    }
 }
@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+/// Esperas de un grupo por un recurso compartido: los usuarios de una prioridad por una
+/// impresora, o las conexiones de un cliente por un lugar en el servidor.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WaitStats {
+    pub count: usize,    // Veces que el grupo obtuvo el recurso
+    pub total: Duration, // Suma de las esperas
+    pub max: Duration,   // Mayor espera
+}
+
+impl WaitStats {
+    /// Registra una espera de `waited` hasta obtener el recurso.
+    pub fn record(&mut self, waited: Duration) {
+        self.count += 1;
+        self.total += waited;
+        self.max = self.max.max(waited);
+    }
+
+    pub fn mean(&self) -> Duration {
+        self.total / self.count.max(1) as u32
+    }
+}